@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Generating build details as a C header.
+
+use std::io::Write;
+
+use super::{BuildDetails, Constant, Entry, STR_TYPE};
+use error::{Error, Result};
+
+impl BuildDetails {
+    /// Writes the build details as a C header of `#define` macros, for C and
+    /// C++ code linked with the crate.
+    ///
+    /// Every value is determined while generating, including those that would
+    /// be read with `env!` in the Rust output. Strings and integers are
+    /// supported, with booleans written as `1` or `0`. Other details, like
+    /// [`BuildDetail::Cfg`](::BuildDetail::Cfg), are left out, as are
+    /// optional details that are missing.
+    pub fn write_c_header(&self, out: &mut dyn Write) -> Result<()> {
+        if self.with_bom {
+            write!(out, "\u{feff}")?;
+        }
+
+        if let Some(banner) = self.banner()? {
+            writeln!(out, "/* {} */", banner.replace("*/", "* /"))?;
+        }
+
+        writeln!(out, "#ifndef BUILD_DETAILS_H")?;
+        writeln!(out, "#define BUILD_DETAILS_H")?;
+
+        for Entry {
+            constant, optional, ..
+        } in self.entries()?
+        {
+            let value = match constant.resolve() {
+                Some(x) => x,
+                None if optional => continue,
+                None => return Err(Error::MissingDetail(constant.name().to_owned())),
+            };
+
+            if let Some(value) = c_value(&*constant, &value) {
+                writeln!(out, "#define {} {}", constant.name(), value)?;
+            }
+        }
+
+        writeln!(out, "#endif")?;
+
+        Ok(())
+    }
+}
+
+/// Formats `value` as a C literal, if the constant's type has an equivalent.
+fn c_value(constant: &dyn Constant, value: &str) -> Option<String> {
+    match constant.value_type() {
+        STR_TYPE => Some(c_string(value)),
+        "bool" => Some(if value == "true" { "1" } else { "0" }.to_owned()),
+        "u8" | "u32" => Some(format!("{}U", value)),
+        "u64" => Some(format!("{}ULL", value)),
+        "i32" => Some(value.to_owned()),
+        "i64" => Some(format!("{}LL", value)),
+        _ => None,
+    }
+}
+
+/// Quotes and escapes `value` as a C string literal.
+///
+/// Anything outside printable ASCII is written as octal escapes of its UTF-8
+/// bytes, since hexadecimal escapes don't have a fixed length in C.
+fn c_string(value: &str) -> String {
+    let mut txt = String::from("\"");
+
+    for byte in value.bytes() {
+        match byte {
+            b'"' => txt.push_str("\\\""),
+            b'\\' => txt.push_str("\\\\"),
+            b'\n' => txt.push_str("\\n"),
+            b'\t' => txt.push_str("\\t"),
+            b'?' => txt.push_str("\\?"),
+            0x20..=0x7e => txt.push(byte as char),
+            _ => txt.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+
+    txt.push('"');
+    txt
+}
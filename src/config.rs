@@ -0,0 +1,105 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Declarative configuration, deserializable with `serde`.
+
+use std::collections::HashMap;
+
+use super::{BuildDetail, BuildDetails};
+
+/// A description of a [`BuildDetails`] instance, suitable for keeping in a
+/// committed file and loading from `build.rs`.
+///
+/// Every field is optional. For example, in TOML:
+///
+/// ```toml
+/// include = ["Version", "Profile"]
+/// require = ["Name"]
+/// serde_derive = true
+/// placeholder_missing = "unknown"
+/// prefix = "BUILD_"
+///
+/// [rename]
+/// Version = "PKG_VERSION"
+///
+/// [profile_specific]
+/// debug = ["GitAuthor"]
+/// ```
+///
+/// Details listed in both `include` and `require` are required. The fields
+/// correspond to the methods of [`BuildDetails`], and `profile_specific` maps
+/// each profile to the details passed to [`BuildDetails::profile_specific`]
+/// with it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    include: Vec<BuildDetail>,
+    require: Vec<BuildDetail>,
+    serde_derive: bool,
+    lazy_build_info: bool,
+    placeholder_missing: Option<String>,
+    detect_duplicates: bool,
+    timestamp_signed: bool,
+    target_cpu_fallback: Option<String>,
+    panic_strategy_fallback: Option<String>,
+    prefix: Option<String>,
+    rename: HashMap<BuildDetail, String>,
+    profile_specific: HashMap<String, Vec<BuildDetail>>,
+}
+
+impl BuildDetails {
+    /// Construct a [`BuildDetails`] instance from a [`Config`], starting from
+    /// [`BuildDetails::none`].
+    pub fn from_config(config: Config) -> Self {
+        let mut details = Self::none();
+
+        for detail in config.include {
+            details.include(detail);
+        }
+
+        for detail in config.require {
+            details.require(detail);
+        }
+
+        details
+            .serde_derive(config.serde_derive)
+            .lazy_build_info(config.lazy_build_info)
+            .detect_duplicates(config.detect_duplicates)
+            .timestamp_signed(config.timestamp_signed);
+
+        if let Some(text) = config.placeholder_missing {
+            details.placeholder_missing(&text);
+        }
+
+        if let Some(fallback) = config.target_cpu_fallback {
+            details.target_cpu_fallback(Some(&fallback));
+        }
+
+        if let Some(fallback) = config.panic_strategy_fallback {
+            details.panic_strategy_fallback(Some(&fallback));
+        }
+
+        if let Some(prefix) = config.prefix {
+            details.prefix(&prefix);
+        }
+
+        for (detail, name) in config.rename {
+            details.rename(detail, &name);
+        }
+
+        for (profile, profile_details) in config.profile_specific {
+            for detail in profile_details {
+                details.profile_specific(&profile, detail);
+            }
+        }
+
+        details
+    }
+}
+
+impl From<Config> for BuildDetails {
+    fn from(config: Config) -> Self {
+        Self::from_config(config)
+    }
+}
@@ -0,0 +1,95 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A persistent counter, shared between builds through a file.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for another build to release the counter.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An exclusive advisory lock on a file, released when dropped.
+///
+/// The OS releases the lock when the process exits, even if it's killed, so
+/// the file left behind doesn't block later builds.
+struct Lock(File);
+
+impl Lock {
+    fn acquire(path: PathBuf) -> io::Result<Self> {
+        let start = Instant::now();
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Lock(file)),
+                Err(TryLockError::WouldBlock) => {
+                    if start.elapsed() > LOCK_TIMEOUT {
+                        return Err(io::Error::new(
+                            ErrorKind::TimedOut,
+                            format!("counter is locked by {}", path.display()),
+                        ));
+                    }
+
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err(TryLockError::Error(e)) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = self.0.unlock();
+    }
+}
+
+/// `path` with `suffix` appended to its file name.
+pub fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Reads the number stored in `path`, stores the next one, and returns it.
+///
+/// A missing file counts as zero, so the first value is 1. Concurrent builds
+/// are serialized by locking a `.lock` file next to the counter, which is
+/// kept between builds, and the new value is written to a temporary file that
+/// is renamed over the old one.
+pub fn increment(path: &Path) -> io::Result<u64> {
+    let _lock = Lock::acquire(sibling(path, ".lock"))?;
+
+    let current = match fs::read_to_string(path) {
+        Ok(text) => text
+            .trim()
+            .parse()
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => 0,
+        Err(e) => return Err(e),
+    };
+
+    let next: u64 = current + 1;
+
+    let tmp = sibling(path, &format!(".{}.tmp", process::id()));
+    let mut file = File::create(&tmp)?;
+    writeln!(file, "{}", next)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path)?;
+
+    Ok(next)
+}
@@ -0,0 +1,25 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A minimal CRC-32 implementation (the IEEE polynomial used by zlib and PNG),
+//! for checksums of generated values.
+
+/// The reversed IEEE 802.3 polynomial.
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+/// The CRC-32 of `data`.
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for byte in data {
+        crc ^= u32::from(*byte);
+
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (POLYNOMIAL & mask);
+        }
+    }
+
+    !crc
+}
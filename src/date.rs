@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Formatting of build times, without depending on a date library.
+
+use std::env;
+use std::process::Command;
+
+/// Converts a number of days since 1970-01-01 to a `(year, month, day)`
+/// date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // From Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Formats `secs` since the Unix epoch in RFC 3339 (like
+/// `2017-07-14T04:40:00+02:00`), shifted to `offset` seconds east of UTC.
+pub fn format_rfc3339(secs: i64, offset: i32) -> String {
+    let local = secs + i64::from(offset);
+    let (year, month, day) = civil_from_days(local.div_euclid(86_400));
+    let time = local.rem_euclid(86_400);
+
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs() / 60;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        sign,
+        offset / 60,
+        offset % 60,
+    )
+}
+
+/// Offset of the local timezone from UTC at `secs`, in seconds east of UTC.
+///
+/// A fixed POSIX `TZ` (like `JST-9`) is handled directly. Anything else,
+/// including daylight saving rules and zone names like `Europe/Paris`, is
+/// left to the `date` command. Returns `None` when neither works.
+pub fn local_offset(secs: i64) -> Option<i32> {
+    if let Ok(tz) = env::var("TZ") {
+        if let Some(offset) = posix_offset(&tz) {
+            return Some(offset);
+        }
+    }
+
+    date_offset(&["-d", &format!("@{}", secs), "+%z"])
+        .or_else(|| date_offset(&["-r", &secs.to_string(), "+%z"]))
+}
+
+/// Parses a POSIX `TZ` without daylight saving rules, like `UTC0`, `EST5`,
+/// or `<+0530>-5:30`.
+fn posix_offset(tz: &str) -> Option<i32> {
+    let rest = if let Some(quoted) = tz.strip_prefix('<') {
+        &quoted[quoted.find('>')? + 1..]
+    } else {
+        let len = tz.find(|c: char| !c.is_ascii_alphabetic())?;
+        if len < 3 {
+            return None;
+        }
+        &tz[len..]
+    };
+
+    let (sign, rest) = match rest.as_bytes().first()? {
+        b'-' => (1, &rest[1..]),
+        b'+' => (-1, &rest[1..]),
+        _ => (-1, rest),
+    };
+
+    let mut seconds = 0;
+    let mut scale = 3600;
+
+    for part in rest.split(':') {
+        if part.is_empty() || part.len() > 2 || scale == 0 {
+            return None;
+        }
+
+        seconds += part.parse::<i32>().ok()? * scale;
+        scale /= 60;
+    }
+
+    // POSIX counts hours west of UTC, so the sign is flipped.
+    Some(sign * seconds)
+}
+
+/// Runs `date` with `args`, parsing its output as a `+hhmm` offset.
+fn date_offset(args: &[&str]) -> Option<i32> {
+    let output = Command::new("date").args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+
+    if text.len() != 5 {
+        return None;
+    }
+
+    let sign = match &text[..1] {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+
+    let hours: i32 = text[1..3].parse().ok()?;
+    let minutes: i32 = text[3..].parse().ok()?;
+
+    Some(sign * (hours * 3600 + minutes * 60))
+}
@@ -7,10 +7,14 @@
 use std::error::Error as StdError;
 use std::fmt;
 use std::io;
+use std::path::PathBuf;
 use std::result::Result as StdResult;
 
+use BuildDetail;
+
 /// Represents errors that can occur while generating the build details.
 #[derive(Debug)]
+#[allow(clippy::manual_non_exhaustive)]
 pub enum Error {
     /// A formatting error.
     Fmt(fmt::Error),
@@ -27,6 +31,42 @@ pub enum Error {
     /// An environment variable required for code generation wasn't set.
     MissingEnv(&'static str),
 
+    /// The same path was generated more than once while duplicate detection
+    /// was enabled.
+    AlreadyGenerated(PathBuf),
+
+    /// The generated code isn't valid Rust. Contains the parser's message.
+    InvalidGeneratedCode(String),
+
+    /// A name given for a generated constant isn't a valid identifier.
+    InvalidConstName(String),
+
+    /// A marker given to
+    /// [`BuildDetails::generate_region`](::BuildDetails::generate_region)
+    /// is missing, repeated, or out of order. Contains the marker.
+    InvalidRegion(String),
+
+    /// A detail was both included and required while
+    /// [`BuildDetails::strict_conflicts`](::BuildDetails::strict_conflicts)
+    /// was enabled.
+    ConflictingRequirement(BuildDetail),
+
+    /// The generated code is longer than the limit set with
+    /// [`BuildDetails::max_output_bytes`](::BuildDetails::max_output_bytes).
+    /// Contains its length in bytes.
+    OutputTooLarge(usize),
+
+    /// Files can't be created in the directory to generate into, usually
+    /// `OUT_DIR`. Contains the directory.
+    OutDirNotWritable(PathBuf),
+
+    /// A `phf` map couldn't be generated. Contains the reason.
+    PhfBuild(String),
+
+    /// The `SOURCE_DATE_EPOCH` environment variable isn't a number of seconds
+    /// since the Unix epoch. Contains its value.
+    InvalidSourceDateEpoch(String),
+
     #[doc(hidden)]
     __Nonexhaustive,
 }
@@ -39,6 +79,19 @@ impl fmt::Display for Error {
             Error::Missing => write!(f, "Missing value"),
             Error::MissingDetail(x) => write!(f, "Missing value: {}", x),
             Error::MissingEnv(x) => write!(f, "A required environment variable is missing: {}", x),
+            Error::AlreadyGenerated(x) => write!(f, "Already generated: {}", x.display()),
+            Error::InvalidGeneratedCode(x) => write!(f, "Generated code is invalid: {}", x),
+            Error::InvalidConstName(x) => write!(f, "Invalid constant name: {:?}", x),
+            Error::InvalidRegion(x) => write!(f, "Invalid region marker: {:?}", x),
+            Error::ConflictingRequirement(x) => {
+                write!(f, "Detail is both included and required: {:?}", x)
+            }
+            Error::OutputTooLarge(x) => write!(f, "Generated code is too large: {} bytes", x),
+            Error::OutDirNotWritable(x) => {
+                write!(f, "Output directory is not writable: {}", x.display())
+            }
+            Error::PhfBuild(x) => write!(f, "Unable to build map: {}", x),
+            Error::InvalidSourceDateEpoch(x) => write!(f, "Invalid SOURCE_DATE_EPOCH: {:?}", x),
             Error::__Nonexhaustive => unreachable!(),
         }
     }
@@ -52,17 +105,35 @@ impl StdError for Error {
             Error::Missing => "missing detail",
             Error::MissingDetail(_) => "missing detail",
             Error::MissingEnv(_) => "missing environment variable",
+            Error::AlreadyGenerated(_) => "already generated",
+            Error::InvalidGeneratedCode(_) => "invalid generated code",
+            Error::InvalidConstName(_) => "invalid constant name",
+            Error::InvalidRegion(_) => "invalid region marker",
+            Error::ConflictingRequirement(_) => "conflicting requirement",
+            Error::OutputTooLarge(_) => "generated code too large",
+            Error::OutDirNotWritable(_) => "output directory not writable",
+            Error::PhfBuild(_) => "unable to build map",
+            Error::InvalidSourceDateEpoch(_) => "invalid SOURCE_DATE_EPOCH",
             Error::__Nonexhaustive => unreachable!(),
         }
     }
 
-    fn cause(&self) -> Option<&StdError> {
+    fn cause(&self) -> Option<&dyn StdError> {
         match self {
             Error::Fmt(ref e) => Some(e),
             Error::Io(ref e) => Some(e),
             Error::Missing => None,
             Error::MissingDetail(_) => None,
             Error::MissingEnv(_) => None,
+            Error::AlreadyGenerated(_) => None,
+            Error::InvalidGeneratedCode(_) => None,
+            Error::InvalidConstName(_) => None,
+            Error::InvalidRegion(_) => None,
+            Error::ConflictingRequirement(_) => None,
+            Error::OutputTooLarge(_) => None,
+            Error::OutDirNotWritable(_) => None,
+            Error::PhfBuild(_) => None,
+            Error::InvalidSourceDateEpoch(_) => None,
             Error::__Nonexhaustive => unreachable!(),
         }
     }
@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Reusable formatters for [`BuildDetails::formatter`](::BuildDetails::formatter).
+
+/// Shortens the value to at most 40 bytes, ending with `...` when anything
+/// was cut, like [`BuildDetails::truncate`](::BuildDetails::truncate).
+pub fn truncate_40(value: &str) -> String {
+    super::truncate(value.to_owned(), 40)
+}
+
+/// Keeps the last component of a path, separated by `/` or `\`, so
+/// `"/home/me/project/"` becomes `"project"`.
+pub fn basename(value: &str) -> String {
+    let trimmed = value.trim_end_matches(['/', '\\']);
+
+    match trimmed.rsplit(['/', '\\']).next() {
+        Some(name) if !name.is_empty() => name.to_owned(),
+        _ => value.to_owned(),
+    }
+}
+
+/// Upgrades an `http://` URL to `https://`, leaving anything else unchanged.
+pub fn https_only(value: &str) -> String {
+    match value.strip_prefix("http://") {
+        Some(rest) => format!("https://{}", rest),
+        None => value.to_owned(),
+    }
+}
@@ -0,0 +1,60 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Helpers for querying the `git` repository being built.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Runs `git` with `args`, returning its output with the trailing newline
+/// removed.
+///
+/// Returns `None` if `git` isn't installed, exits unsuccessfully (for example
+/// when not run inside a repository), or prints something that isn't UTF-8.
+pub fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+
+    Some(stdout.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// The absolute path of `name` in the repository's `.git` directory, like
+/// `refs/stash`, whether or not it exists.
+pub fn git_path(name: &str) -> Option<PathBuf> {
+    let path = git(&["rev-parse", "--git-path", name])?;
+
+    // Relative paths are relative to the working directory.
+    Some(env::current_dir().ok()?.join(path))
+}
+
+/// The files to watch so [`super::BuildDetail::GitStashCount`] is updated
+/// when a stash is pushed or dropped.
+///
+/// `git stash list` reads the reflog of `refs/stash`, which only exists once
+/// something was stashed. Until then, the directory holding the reflogs is
+/// watched instead, since Cargo treats a missing file as always changed.
+pub fn stash_paths() -> Vec<PathBuf> {
+    let existing: Vec<_> = ["logs/refs/stash", "refs/stash"]
+        .iter()
+        .filter_map(|x| git_path(x))
+        .filter(|x| x.exists())
+        .collect();
+
+    if !existing.is_empty() {
+        return existing;
+    }
+
+    ["logs/refs", "refs"]
+        .iter()
+        .filter_map(|x| git_path(x))
+        .find(|x| x.exists())
+        .into_iter()
+        .collect()
+}
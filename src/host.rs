@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Information about the machine running `build.rs`.
+
+use std::env::consts;
+use std::fs;
+use std::process::Command;
+
+/// Best-effort version of the host operating system, like `"Ubuntu 22.04 LTS"`
+/// or `"14.2.1"`.
+fn os_version() -> Option<String> {
+    let version = if cfg!(target_os = "linux") {
+        let release = fs::read_to_string("/etc/os-release").ok()?;
+
+        release
+            .lines()
+            .filter_map(|line| line.strip_prefix("PRETTY_NAME="))
+            .map(|x| x.trim_matches('"').to_owned())
+            .next()?
+    } else if cfg!(target_os = "macos") {
+        command("sw_vers", &["-productVersion"])?
+    } else if cfg!(windows) {
+        command("cmd", &["/C", "ver"])?
+    } else {
+        command("uname", &["-r"])?
+    };
+
+    let version = version.trim();
+
+    if version.is_empty() {
+        None
+    } else {
+        Some(version.to_owned())
+    }
+}
+
+fn command(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+/// The host operating system, followed by its version when it can be found.
+pub fn os() -> String {
+    match os_version() {
+        Some(version) => format!("{} {}", consts::OS, version),
+        None => consts::OS.to_owned(),
+    }
+}
@@ -53,32 +53,148 @@
 //! ```no_compile
 //! extern crate phf;
 //! ```
+//!
+//! When `phf` is available under another path, set it with
+//! [`BuildDetails::phf_path`].
+//!
+//! ## A note on [`BuildDetails::serde_derive`]
+//!
+//! The generated `BuildInfo` struct derives `::serde::Serialize`, so the crate
+//! including it needs a runtime dependency on `serde` with the `derive`
+//! feature. [`BuildDetail::Cfg`] is left out of the struct.
+//!
+//! In `Cargo.toml`, add:
+//!
+//! ```toml
+//! [dependencies]
+//! serde = { version = "1.0", features = ["derive"] }
+//! ```
+//!
+//! In `src/lib.rs` or `src/main.rs`:
+//!
+//! ```no_compile
+//! extern crate serde;
+//! ```
+//!
+//! ## Configuration files
+//!
+//! With the `config` feature enabled, a [`BuildDetails`] can be built from a
+//! [`Config`], which can be deserialized from any format supported by `serde`.
+//! This lets the configuration live in a committed file, like
+//! `build_details.toml`, instead of in `build.rs`.
 #![deny(
     missing_debug_implementations, missing_docs, trivial_casts, trivial_numeric_casts,
     unused_extern_crates, unused_import_braces, unused_qualifications
 )]
+#![allow(clippy::new_ret_no_self)]
 
 #[macro_use]
 extern crate maplit;
+#[allow(unused_extern_crates)]
 extern crate phf_codegen;
+#[cfg(feature = "config")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "verify")]
+extern crate syn;
 
+mod c_header;
+#[cfg(feature = "config")]
+mod config;
+mod counter;
+mod crc32;
+mod date;
 pub mod error;
+pub mod formatters;
+mod git;
+mod host;
+mod manifest;
+mod nonce;
+mod region;
+mod repro;
+pub mod runtime;
+mod rustc;
+mod rustflags;
+mod sha256;
+mod source;
+mod vendor;
+#[cfg(feature = "verify")]
+mod verify;
+mod version;
 
+#[cfg(feature = "config")]
+pub use config::Config;
 use error::*;
+pub use repro::{ReproIssue, ReproOutput, ReproReport};
+use rustflags::RustFlags;
 
-use std::collections::{HashMap, HashSet};
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::env;
 use std::fmt;
-use std::fs::File;
-use std::io::Write;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::panic;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Code generator for build details. See the crate documentation for an example.
 #[derive(Debug, Clone)]
 pub struct BuildDetails {
     optional: HashSet<BuildDetail>,
     required: HashSet<BuildDetail>,
+    serde_derive: bool,
+    placeholder: Option<String>,
+    detect_duplicates: bool,
+    lazy_build_info: bool,
+    timestamp_signed: bool,
+    target_cpu_fallback: Option<String>,
+    panic_strategy_fallback: Option<String>,
+    namespaced: bool,
+    detail_manifest: bool,
+    version_gate: bool,
+    semver_accessor: bool,
+    optional_wrapper: Option<OptionalWrapper>,
+    struct_derives: Vec<String>,
+    phf_path: String,
+    ordered_cfg: bool,
+    warn_on_missing: bool,
+    with_source_path: bool,
+    strict_conflicts: bool,
+    max_output_bytes: Option<usize>,
+    use_static: bool,
+    as_cow: bool,
+    output_dir: Option<PathBuf>,
+    post_process: Option<PostProcess>,
+    smart_version_template: String,
+    order: Vec<BuildDetail>,
+    newtypes: bool,
+    assert_git_in_release: bool,
+    output_banner: bool,
+    with_bom: bool,
+    build_flags: bool,
+    defer_missing: bool,
+    target_label: Option<String>,
+    value_checksums: bool,
+    impl_on: Option<String>,
+    list_format: ListFormat,
+    none_as: Option<String>,
+    gen_timing: bool,
+    docs_rs_safe: Option<bool>,
+    prefix: Option<String>,
+    renames: HashMap<BuildDetail, String>,
+    requested: HashMap<BuildDetail, bool>,
+    conflicts: HashSet<BuildDetail>,
+    transforms: HashMap<BuildDetail, Transform>,
+    truncations: HashMap<BuildDetail, usize>,
+    profiles: HashMap<BuildDetail, Vec<String>>,
+    extras: Vec<Extra>,
+    #[cfg(feature = "verify")]
+    verify: bool,
 }
 
 impl Default for BuildDetails {
@@ -89,12 +205,27 @@ impl Default for BuildDetails {
                 BuildDetail::Profile,
                 BuildDetail::RustFlags,
             ],
-            required: HashSet::new(),
+            ..Self::none()
         }
     }
 }
 
 impl BuildDetails {
+    /// Construct a [`BuildDetails`] instance with only [`BuildDetail::Version`]
+    /// and [`BuildDetail::GitCommitShort`] marked as optional, the smallest
+    /// useful build stamp for size-conscious binaries.
+    ///
+    /// Every string detail is a `&'static str` constant, which `rustc` only
+    /// stores once no matter how often it's used, and identical strings share
+    /// storage. The `BuildInfo` struct, the detail manifest, and maps like
+    /// [`BuildDetail::Cfg`] are left out unless enabled.
+    pub fn minimal() -> Self {
+        Self {
+            optional: hashset![BuildDetail::Version, BuildDetail::GitCommitShort],
+            ..Self::none()
+        }
+    }
+
     /// Construct a [`BuildDetails`] instance with all available details marked
     /// as optional.
     pub fn all() -> Self {
@@ -110,8 +241,18 @@ impl BuildDetails {
                 BuildDetail::Homepage,
                 BuildDetail::Cfg,
                 BuildDetail::Features,
+                BuildDetail::CodegenUnits,
+                BuildDetail::Linker,
+                BuildDetail::TargetCpu,
+                BuildDetail::Incremental,
+                BuildDetail::PanicStrategy,
+                BuildDetail::FeaturesString,
+                BuildDetail::Sanitizers,
+                BuildDetail::StaticCrt,
+                BuildDetail::LtoKind,
+                BuildDetail::AuthorCount,
             ],
-            required: HashSet::new(),
+            ..Self::none()
         }
     }
 
@@ -124,12 +265,41 @@ impl BuildDetails {
     /// It is impossible to use this method and not break API compatibility if
     /// new [`BuildDetail`] variants are added.
     #[doc(hidden)]
+    #[deprecated(
+        since = "0.1.3",
+        note = "requires every new detail on upgrade; use `require_these` instead"
+    )]
     pub fn require_all() -> Self {
         let mut x = Self::all();
         ::std::mem::swap(&mut x.optional, &mut x.required);
         x
     }
 
+    /// Construct a [`BuildDetails`] instance with exactly `details` marked as
+    /// required.
+    ///
+    /// Unlike `require_all`, the required set doesn't grow when new
+    /// [`BuildDetail`] variants are added, so upgrading never starts failing
+    /// builds that can't provide a new detail. To migrate, list the details
+    /// that were left after the calls to [`BuildDetails::exclude`]:
+    ///
+    /// ```no_run
+    /// # use build_details::{BuildDetail, BuildDetails};
+    /// BuildDetails::require_these(&[
+    ///     BuildDetail::Version,
+    ///     BuildDetail::Profile,
+    ///     BuildDetail::Timestamp,
+    /// ])
+    /// .generate("build_details.rs")
+    /// .unwrap();
+    /// ```
+    pub fn require_these(details: &[BuildDetail]) -> Self {
+        Self {
+            required: details.iter().copied().collect(),
+            ..Self::none()
+        }
+    }
+
     /// Construct a [`BuildDetails`] instance with no included details.
     ///
     /// This method isn't particularly useful by itself, and will probably need
@@ -138,6 +308,57 @@ impl BuildDetails {
         Self {
             optional: HashSet::new(),
             required: HashSet::new(),
+            serde_derive: false,
+            placeholder: None,
+            detect_duplicates: false,
+            lazy_build_info: false,
+            timestamp_signed: false,
+            target_cpu_fallback: Some("default".to_owned()),
+            panic_strategy_fallback: Some("unwind".to_owned()),
+            namespaced: false,
+            detail_manifest: false,
+            version_gate: false,
+            semver_accessor: false,
+            optional_wrapper: None,
+            phf_path: "::phf".to_owned(),
+            ordered_cfg: false,
+            warn_on_missing: false,
+            with_source_path: false,
+            strict_conflicts: false,
+            max_output_bytes: None,
+            use_static: false,
+            as_cow: false,
+            output_dir: None,
+            post_process: None,
+            smart_version_template: "{version}-{git_short}{dirty}".to_owned(),
+            order: vec![],
+            newtypes: false,
+            assert_git_in_release: false,
+            output_banner: false,
+            with_bom: false,
+            build_flags: false,
+            defer_missing: false,
+            target_label: None,
+            value_checksums: false,
+            impl_on: None,
+            list_format: ListFormat::MultiLine,
+            none_as: None,
+            gen_timing: false,
+            docs_rs_safe: None,
+            prefix: None,
+            renames: HashMap::new(),
+            requested: HashMap::new(),
+            conflicts: HashSet::new(),
+            struct_derives: ["Debug", "Clone", "Copy", "PartialEq", "Eq"]
+                .iter()
+                .map(|x| (*x).to_owned())
+                .collect(),
+            transforms: HashMap::new(),
+            truncations: HashMap::new(),
+            profiles: HashMap::new(),
+            extras: vec![],
+            #[cfg(feature = "verify")]
+            verify: false,
         }
     }
 
@@ -146,6 +367,10 @@ impl BuildDetails {
     /// If a detail is marked as required and isn't available at build time, the
     /// build will fail.
     pub fn require(&mut self, detail: BuildDetail) -> &mut Self {
+        if self.requested.insert(detail, true) == Some(false) {
+            self.conflicts.insert(detail);
+        }
+
         self.optional.remove(&detail);
         self.required.insert(detail);
         self
@@ -156,6 +381,10 @@ impl BuildDetails {
     /// If a detail is marked as optional and isn't available at build time, the
     /// generated value will be `None`.
     pub fn include(&mut self, detail: BuildDetail) -> &mut Self {
+        if self.requested.insert(detail, false) == Some(true) {
+            self.conflicts.insert(detail);
+        }
+
         self.required.remove(&detail);
         self.optional.insert(detail);
         self
@@ -163,265 +392,3676 @@ impl BuildDetails {
 
     /// Exclude a [`BuildDetail`]. It will not show up in the generated output.
     pub fn exclude(&mut self, detail: BuildDetail) -> &mut Self {
+        self.requested.remove(&detail);
+        self.conflicts.remove(&detail);
         self.required.remove(&detail);
         self.optional.remove(&detail);
         self
     }
 
-    /// Creates a file called `path` in the build's `OUT_DIR` directory. See
-    /// the crate documentation for an example.
-    pub fn generate<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let out_dir = match env::var_os("OUT_DIR") {
-            Some(x) => x,
-            None => return Err(Error::MissingEnv("OUT_DIR")),
-        };
-
-        let mut out_path = PathBuf::from(out_dir);
-        out_path.push(path);
-
-        let mut out_file = File::create(out_path)?;
-
-        self.write_to(&mut out_file)
+    /// Fail generation with [`Error::ConflictingRequirement`] when a detail was
+    /// passed to both [`BuildDetails::include`] and [`BuildDetails::require`],
+    /// instead of keeping whichever call came last.
+    ///
+    /// This catches policies that disagree, like a shared helper including a
+    /// detail that the build script requires. Details marked by a constructor
+    /// like [`BuildDetails::all`] don't count, and
+    /// [`BuildDetails::exclude`] forgets earlier calls.
+    pub fn strict_conflicts(&mut self, enabled: bool) -> &mut Self {
+        self.strict_conflicts = enabled;
+        self
     }
 
-    /// Writes the generated code to a [`::std::io::Write'] instead of to a file.
-    pub fn write_to(&self, out_file: &mut Write) -> Result<()> {
-        for detail in &self.optional {
-            writeln!(out_file, "{}", detail.render_option()?)?;
-        }
-
-        for detail in &self.required {
-            writeln!(out_file, "{}", detail.render()?)?;
+    /// Include a [`BuildDetail`] as optional if `predicate` returns `true` for
+    /// the target being built, and exclude it otherwise.
+    ///
+    /// The predicate is called immediately, in `build.rs`, so the decision is
+    /// made at build time rather than with `#[cfg]` in the generated code.
+    ///
+    /// ```no_run
+    /// # use build_details::{BuildDetail, BuildDetails};
+    /// BuildDetails::default()
+    ///     .only_for_target(BuildDetail::Linker, |t| t.has_family("windows"))
+    ///     .generate("build_details.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn only_for_target<F>(&mut self, detail: BuildDetail, predicate: F) -> &mut Self
+    where
+        F: FnOnce(&Target) -> bool,
+    {
+        if predicate(&Target::from_env()) {
+            self.include(detail)
+        } else {
+            self.exclude(detail)
         }
-
-        Ok(())
     }
-}
-
-/// List of build details that can be included in the generated code.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-pub enum BuildDetail {
-    /// Number of seconds since [`::std::time::UNIX_EPOCH`]
-    Timestamp,
 
-    /// Equivalent to the `CARGO_PKG_VERSION` environment variable.
-    Version,
-
-    /// Equivalent to `PROFILE` in environment variables passed to `build.rs'.
+    /// Only generate a [`BuildDetail`] when the build's `PROFILE` is
+    /// `profile`, like keeping verbose details out of release builds.
     ///
-    /// Should usually be `"debug"` or `"release"`.
-    Profile,
-
-    /// Equivalent to the `RUSTFLAGS` environment variable.
+    /// The detail is included as optional unless it was already included or
+    /// required, and calling this again for the same detail allows another
+    /// profile. [`BuildDetails::exclude`] still removes the detail entirely.
     ///
-    /// Note that this isn't _all_ of the flags passed to `rustc`, but instead
-    /// it is only the custom extra flags.
-    RustFlags,
+    /// ```no_run
+    /// # use build_details::{BuildDetail, BuildDetails};
+    /// BuildDetails::default()
+    ///     .profile_specific("debug", BuildDetail::GitAuthor)
+    ///     .generate("build_details.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn profile_specific(&mut self, profile: &str, detail: BuildDetail) -> &mut Self {
+        self.profiles
+            .entry(detail)
+            .or_default()
+            .push(profile.to_owned());
 
-    /// Equivalent to the `CARGO_PKG_NAME` environment variable.
-    Name,
+        if !self.required.contains(&detail) {
+            self.optional.insert(detail);
+        }
 
-    /// Equivalent to the `CARGO_PKG_AUTHORS` environment variable.
-    Authors,
+        self
+    }
 
-    /// Equivalent to the `CARGO_PKG_DESCRIPTION` environment variable.
-    Description,
+    /// Require [`BuildDetail::GitCommitShort`] in release builds, so every
+    /// release carries the commit it was built from.
+    ///
+    /// When the build's `PROFILE` is `release`, the detail is generated even if
+    /// it wasn't included, generation fails if `git` can't resolve `HEAD`, and
+    /// the generated code checks at compile time that `GIT_COMMIT_SHORT` is a
+    /// plain `&'static str`. Other profiles keep the detail as configured.
+    ///
+    /// Builds from a source tarball, including crates downloaded from a
+    /// registry, have no `.git` directory, so their release builds fail. Only
+    /// enable this where releases are built from a checkout.
+    pub fn assert_git_in_release(&mut self, enabled: bool) -> &mut Self {
+        self.assert_git_in_release = enabled;
+        self
+    }
 
-    /// Equivalent to the `CARGO_PKG_HOMEPAGE` environment variable.
-    Homepage,
+    /// Whether [`BuildDetails::assert_git_in_release`] applies to this build.
+    fn release_requires_git(&self) -> bool {
+        self.assert_git_in_release && env::var("PROFILE").is_ok_and(|x| x == "release")
+    }
 
-    /// Equivalent to the `OPT_LEVEL` environment variable in `build.rs`.
-    OptLevel,
+    /// Whether [`BuildDetails::docs_rs_safe`] applies to this build.
+    fn docs_safe(&self) -> bool {
+        self.docs_rs_safe
+            .unwrap_or_else(|| env::var_os("DOCS_RS").is_some())
+    }
 
-    /// Equivalent to the `CARGO_CFG_*` environment variables in `build.rs`.
-    Cfg,
+    /// The details to generate, and whether each is required, in the order
+    /// set with [`BuildDetails::order`].
+    fn selected(&self) -> impl Iterator<Item = (BuildDetail, bool)> {
+        let profile = env::var("PROFILE").ok();
 
-    /// Equivalent to the `CARGO_FEATURE_*` environment variables in `build.rs`.
-    Features,
+        let optional = self.optional.iter().map(|x| (*x, false));
+        let required = self.required.iter().map(|x| (*x, true));
 
-    #[doc(hidden)]
-    __Nonexhaustive,
-}
+        let mut selected: Vec<_> = optional
+            .chain(required)
+            .filter(|(detail, _)| match self.profiles.get(detail) {
+                Some(profiles) => profiles.iter().any(|x| Some(x) == profile.as_ref()),
+                None => true,
+            })
+            .collect();
 
-impl BuildDetail {
-    fn into_render(self) -> Box<Render> {
-        use self::BuildDetail::*;
+        if self.release_requires_git() {
+            selected.retain(|(detail, _)| *detail != BuildDetail::GitCommitShort);
+            selected.push((BuildDetail::GitCommitShort, true));
+        }
 
-        match self {
-            Timestamp => Box::from(self::Timestamp::new()),
+        selected.sort_by_key(|(detail, _)| {
+            self.order
+                .iter()
+                .position(|x| x == detail)
+                .unwrap_or(self.order.len())
+        });
 
-            Version => Box::from(Env::new("VERSION", "CARGO_PKG_VERSION")),
-            Name => Box::from(Env::new("NAME", "CARGO_PKG_NAME")),
-            Authors => Box::from(Env::new("AUTHORS", "CARGO_PKG_AUTHORS")),
-            Description => Box::from(Env::new("DESCRIPTION", "CARGO_PKG_DESCRIPTION")),
-            Homepage => Box::from(Env::new("HOMEPAGE", "CARGO_PKG_HOMEPAGE")),
-            RustFlags => Box::from(Env::new("RUST_FLAGS", "RUSTFLAGS")),
+        selected.into_iter()
+    }
 
-            Profile => Box::from(BuildEnv::new("PROFILE", "PROFILE")),
-            OptLevel => Box::from(BuildEnv::new("OPT_LEVEL", "OPT_LEVEL")),
+    /// Generate the details in `order` first, in that order, followed by the
+    /// rest in the usual order.
+    ///
+    /// This gives a committed generated file, or the fields of the generated
+    /// `BuildInfo` struct, a fixed layout. Details in `order` that aren't
+    /// included are ignored.
+    pub fn order(&mut self, order: &[BuildDetail]) -> &mut Self {
+        self.order = order.to_vec();
+        self
+    }
 
-            Cfg => Box::from(BuildEnvMap::new("CFG", "CARGO_CFG_")),
-            Features => Box::from(BuildEnvList::new("FEATURES", "CARGO_FEATURE_")),
+    /// Additionally generate a `BuildInfo` struct deriving `serde::Serialize`,
+    /// and a `BUILD_INFO` constant holding every included detail except
+    /// [`BuildDetail::Cfg`].
+    ///
+    /// See the crate documentation for the required runtime dependency.
+    pub fn serde_derive(&mut self, enabled: bool) -> &mut Self {
+        self.serde_derive = enabled;
+        self
+    }
 
-            __Nonexhaustive => unreachable!(),
-        }
+    /// Generate into `dir` instead of the build's `OUT_DIR`, for builds where
+    /// `OUT_DIR` can't be written to.
+    ///
+    /// Relative paths given to [`BuildDetails::generate`] are resolved
+    /// against `dir`, and [`BuildDetails::detect_duplicates`] keeps its
+    /// record there.
+    pub fn output_dir(&mut self, dir: PathBuf) -> &mut Self {
+        self.output_dir = Some(dir);
+        self
     }
-}
 
-impl Render for BuildDetail {
-    fn render_option(&self) -> Result<String> {
-        self.into_render().render_option()
+    /// Format of [`BuildDetail::SmartVersion`] for builds that aren't of a
+    /// clean, tagged commit. Defaults to `{version}-{git_short}{dirty}`.
+    ///
+    /// `{version}` is the package version, `{git_short}` the abbreviated
+    /// `HEAD` commit, and `{dirty}` is `-dirty` when there are uncommitted
+    /// changes to tracked files, and empty otherwise.
+    pub fn smart_version_template(&mut self, template: &str) -> &mut Self {
+        self.smart_version_template = template.to_owned();
+        self
     }
 
-    fn render(&self) -> Result<String> {
-        self.into_render().render()
+    /// Generate each detail as a `pub static` instead of a `pub const`.
+    ///
+    /// A `const` is copied into every place it's used, while a `static` is
+    /// stored once, which matters for large values like an included file.
+    /// Other generated items, like `BUILD_INFO`, stay `const`.
+    pub fn use_static(&mut self, enabled: bool) -> &mut Self {
+        self.use_static = enabled;
+        self
     }
-}
 
-struct Detail<T>
-where
-    T: Render,
-{
-    name: &'static str,
-    value_type: &'static str,
-    value: T,
-}
+    /// Choose how lists, like [`BuildDetail::Authors`], are laid out in the
+    /// generated code. Defaults to [`ListFormat::MultiLine`].
+    pub fn list_format(&mut self, format: ListFormat) -> &mut Self {
+        self.list_format = format;
+        self
+    }
 
-impl<T> Render for Detail<T>
-where
-    T: Render,
-{
-    fn render_option(&self) -> Result<String> {
-        let value = self.value.render_option()?;
+    /// Generate string details as `Cow<'static, str>` instead of
+    /// `&'static str`, like
+    /// `pub const PROFILE: Cow<'static, str> = Cow::Borrowed("release");`, so
+    /// they can be stored alongside values overridden at runtime.
+    ///
+    /// Optional details become `Option<Cow<'static, str>>`, and details using
+    /// [`BuildDetails::optional_wrapper`] are left as they are. A `Cow` isn't
+    /// `Copy`, so the `BuildInfo` struct needs
+    /// [`BuildDetails::struct_derives`] without `Copy`, and
+    /// [`BuildDetails::newtypes`] doesn't apply.
+    pub fn as_cow(&mut self, enabled: bool) -> &mut Self {
+        self.as_cow = enabled;
+        self
+    }
 
-        Ok(format!(
-            "pub const {}: Option<{}> = {};",
-            self.name, self.value_type, value
-        ))
+    /// Fail with [`Error::OutputTooLarge`] instead of writing generated code
+    /// longer than `max` bytes.
+    ///
+    /// This guards against generated files that slow down compilation, like
+    /// an unexpectedly large [`BuildDetail::Cfg`] map. There is no limit by
+    /// default.
+    pub fn max_output_bytes(&mut self, max: usize) -> &mut Self {
+        self.max_output_bytes = Some(max);
+        self
     }
 
-    fn render(&self) -> Result<String> {
-        let value = match self.value.render() {
-            Ok(x) => x,
-            Err(Error::Missing) => {
-                return Err(Error::MissingDetail(self.name.to_owned()));
-            }
-            e => return e,
-        };
+    /// Emit `GENERATED_FROM`, the path passed to [`BuildDetails::generate`],
+    /// so each of several generated modules can tell where it came from.
+    ///
+    /// The path is relative to `OUT_DIR`, as passed. Only
+    /// [`BuildDetails::generate`] emits the constant.
+    pub fn with_source_path(&mut self, enabled: bool) -> &mut Self {
+        self.with_source_path = enabled;
+        self
+    }
 
-        Ok(format!(
-            "pub const {}: {} = {};",
-            self.name, self.value_type, value
-        ))
+    /// Tag the output with `label`, like a target triple when the crate is
+    /// built for several targets, so the results can be told apart and
+    /// collected side by side.
+    ///
+    /// The label is emitted as `TARGET_LABEL`, and [`BuildDetails::generate`]
+    /// inserts it before the extension of the file name, so
+    /// `build_details.rs` becomes `build_details.aarch64-linux.rs`. The label
+    /// must be usable in a file name.
+    pub fn with_target_label(&mut self, label: &str) -> &mut Self {
+        self.target_label = Some(label.to_owned());
+        self
     }
-}
 
-trait Render {
-    fn render_option(&self) -> Result<String>;
-    fn render(&self) -> Result<String>;
-}
+    /// Emit `BUILD_DETAILS_GEN_MS: u64`, the number of milliseconds spent
+    /// resolving and rendering the details, to keep an eye on what
+    /// generating them adds to build times.
+    ///
+    /// This includes running external commands like `git` and `rustc`. The
+    /// value differs between builds of the same source, so
+    /// [`BuildDetails::reproducibility_report`] lists it among its
+    /// [`ReproReport::outputs`].
+    pub fn with_gen_timing(&mut self, enabled: bool) -> &mut Self {
+        self.gen_timing = enabled;
+        self
+    }
 
-impl<T> Render for Option<T>
-where
-    T: fmt::Display,
-{
-    fn render_option(&self) -> Result<String> {
-        match self {
-            Some(x) => Ok(format!("Some({})", x)),
-            None => Ok(format!("None")),
-        }
+    /// Generate stable placeholders instead of the details that depend on the
+    /// machine or tools building the crate, so documentation builds are
+    /// deterministic and don't fail when, say, `git` isn't installed.
+    ///
+    /// Unless set, this is enabled when the `DOCS_RS` environment variable
+    /// is, which is how docs.rs identifies its builds. While it's set and a
+    /// detail could be replaced, [`BuildDetails::generate`] prints a
+    /// `cargo:rerun-if-env-changed` directive for it, so the real details
+    /// come back once it's unset. Ordinary builds don't watch it, since a
+    /// watched variable that's never set makes cargo rerun the script on
+    /// every build.
+    ///
+    /// Optional details become `None`, and required ones an empty string or
+    /// list, zero, or `false`, depending on their type. Replaced details
+    /// aren't determined at all, so no external commands are run, and they
+    /// print no directives of their own. Details from the package's metadata
+    /// (in the `"pkg"` category of [`BuildDetails::namespaced`]),
+    /// [`BuildDetail::Cfg`], [`BuildDetail::ProfileOverrides`], and constants
+    /// added with methods like [`BuildDetails::include_env_list`] are left
+    /// alone.
+    pub fn docs_rs_safe(&mut self, enabled: bool) -> &mut Self {
+        self.docs_rs_safe = Some(enabled);
+        self
     }
 
-    fn render(&self) -> Result<String> {
-        match self {
-            Some(x) => Ok(format!("{}", x)),
-            None => Err(Error::Missing),
-        }
+    /// Follow each string constant with a `NAME_CRC: u32` constant holding the
+    /// CRC-32 of its UTF-8 bytes, so corruption of the embedded values can be
+    /// detected at runtime.
+    ///
+    /// The CRC-32 is the common one used by zlib and PNG. Optional constants
+    /// get an `Option<u32>`, which is `None` when the value is missing. This
+    /// roughly doubles the number of constants, so it's off by default.
+    pub fn with_value_checksums(&mut self, enabled: bool) -> &mut Self {
+        self.value_checksums = enabled;
+        self
     }
-}
 
-struct Timestamp;
+    /// Print a `cargo:warning` naming each optional detail that is missing,
+    /// so details like an unset [`BuildDetail::Homepage`] don't go unnoticed.
+    ///
+    /// The generated code is unchanged, and the warnings are only printed by
+    /// [`BuildDetails::generate`].
+    pub fn warn_on_missing(&mut self, enabled: bool) -> &mut Self {
+        self.warn_on_missing = enabled;
+        self
+    }
 
-impl Timestamp {
-    pub fn new() -> Detail<Option<u64>> {
-        // TODO: Touch build.rs to trigger a rebuild every time
+    /// Instead of failing generation when a required detail is missing,
+    /// generate a constant that fails to compile only where it is used.
+    ///
+    /// The constant has the type `MissingBuildDetail`, an empty struct
+    /// generated alongside it, so using it as the detail's type is an error,
+    /// and it is `#[deprecated]` with a note naming the missing detail. A crate
+    /// can then require a detail for the code that needs it, and still build
+    /// where the detail is legitimately unavailable, like a source tarball
+    /// without `.git`.
+    ///
+    /// The error moves from the build script to the crate being compiled, and
+    /// only happens if the constant is used in a way that needs its value, so
+    /// a missing detail is noticed later, and may not be noticed at all. The
+    /// missing constant is left out of the `BuildInfo` struct, and
+    /// [`BuildDetails::write_c_header`] still fails.
+    pub fn defer_missing(&mut self, enabled: bool) -> &mut Self {
+        self.defer_missing = enabled;
+        self
+    }
 
-        let secs = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .as_ref()
-            .map(Duration::as_secs)
-            .ok();
+    /// Path of the `phf` crate in the generated code for [`BuildDetail::Cfg`],
+    /// like `::my_crate::reexports::phf`. Defaults to `::phf`.
+    pub fn phf_path(&mut self, path: &str) -> &mut Self {
+        self.phf_path = path.to_owned();
+        self
+    }
 
-        Detail {
-            name: "TIMESTAMP",
-            value_type: "u64",
-            value: secs,
-        }
+    /// Generate [`BuildDetail::Cfg`] as a `phf::OrderedMap` sorted by key,
+    /// instead of a `phf::Map`, so iterating over it gives a stable order.
+    ///
+    /// Lookups in a `phf::Map` are slightly faster, so it stays the default.
+    pub fn ordered_cfg(&mut self, enabled: bool) -> &mut Self {
+        self.ordered_cfg = enabled;
+        self
     }
-}
 
-struct Env(&'static str);
+    /// Additionally generate `BUILD_FLAGS: u32`, packing the selected boolean
+    /// details into one value to log or compare, with a `BUILD_FLAG_*` mask
+    /// constant for each of them.
+    ///
+    /// Each boolean detail has a fixed bit, whether or not others are selected:
+    ///
+    ///   * bit 0, `BUILD_FLAG_INCREMENTAL`, for [`BuildDetail::Incremental`];
+    ///   * bit 1, `BUILD_FLAG_STATIC_CRT`, for [`BuildDetail::StaticCrt`];
+    ///   * bit 2, `BUILD_FLAG_VENDOR_DIRTY`, for [`BuildDetail::VendorDirty`];
+    ///     and
+    ///   * bit 3, `BUILD_FLAG_CARGO_OFFLINE`, for [`BuildDetail::CargoOffline`].
+    ///
+    /// Details that aren't selected get no mask, and their bit is never set.
+    /// Neither is the bit of an optional detail that is missing.
+    ///
+    /// ```ignore
+    /// if build_details::BUILD_FLAGS & build_details::BUILD_FLAG_STATIC_CRT != 0 {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn build_flags(&mut self, enabled: bool) -> &mut Self {
+        self.build_flags = enabled;
+        self
+    }
 
-impl Render for Env {
-    fn render_option(&self) -> Result<String> {
-        Ok(format!("option_env!(\"{}\")", self.0))
+    /// Start files that aren't Rust, like the one written by
+    /// [`BuildDetails::write_c_header`], with a comment naming the generator
+    /// and when it ran, like
+    /// `/* Generated by build_details 0.1.3 at 2017-07-14T02:40:00+00:00 */`.
+    ///
+    /// The time is in UTC, and honours `SOURCE_DATE_EPOCH`.
+    pub fn output_banner(&mut self, enabled: bool) -> &mut Self {
+        self.output_banner = enabled;
+        self
     }
 
-    fn render(&self) -> Result<String> {
-        Ok(format!("env!(\"{}\")", self.0))
+    /// Start files that aren't Rust, like the one written by
+    /// [`BuildDetails::write_c_header`], with a UTF-8 byte order mark, for
+    /// tools on Windows that need one to recognize the encoding.
+    ///
+    /// Off by default. Rust output never gets a byte order mark, and compilers
+    /// for other languages may not accept one, so only enable this for files
+    /// read by tools that expect it.
+    pub fn with_bom(&mut self, enabled: bool) -> &mut Self {
+        self.with_bom = enabled;
+        self
     }
-}
 
-impl Env {
-    pub fn new(name: &'static str, env: &'static str) -> Detail<Env> {
-        Detail {
-            name,
-            value_type: "&'static str",
-            value: Env(env),
+    /// The text of the comment enabled with [`BuildDetails::output_banner`],
+    /// without comment syntax, or `None` when it is disabled.
+    fn banner(&self) -> Result<Option<String>> {
+        if !self.output_banner {
+            return Ok(None);
         }
-    }
-}
 
-struct BuildEnv(Option<String>);
+        let mut banner = format!("Generated by build_details {}", env!("CARGO_PKG_VERSION"));
 
-impl Render for BuildEnv {
-    fn render_option(&self) -> Result<String> {
-        match self.0 {
-            Some(ref x) => Ok(format!("Some({:?})", x)),
-            None => Ok("None".to_owned()),
+        if let Some(secs) = build_time()?.and_then(|x| i64::try_from(x).ok()) {
+            banner.push_str(" at ");
+            banner.push_str(&date::format_rfc3339(secs, 0));
         }
-    }
 
-    fn render(&self) -> Result<String> {
-        match self.0 {
-            Some(ref x) => Ok(format!("{:?}", x)),
-            None => Err(Error::Missing),
-        }
+        Ok(Some(banner))
     }
-}
-
-impl BuildEnv {
-    pub fn new(name: &'static str, env: &'static str) -> Detail<Self> {
-        let env = env::var(env).ok();
 
-        Detail {
-            name,
-            value_type: "&'static str",
-            value: BuildEnv(env),
-        }
+    /// Wrap each string field of the generated `BuildInfo` struct in a newtype
+    /// named after it, like `pub struct Version(pub &'static str);`, so
+    /// different details can't be mixed up.
+    ///
+    /// The newtypes derive the same traits as `BuildInfo`. Only the struct is
+    /// affected, not the constants, and fields using
+    /// [`BuildDetails::optional_wrapper`] are left as they are.
+    pub fn newtypes(&mut self, enabled: bool) -> &mut Self {
+        self.newtypes = enabled;
+        self
     }
-}
 
-fn find_matching_vars(prefix: &'static str) -> HashMap<String, String> {
-    env::vars()
-        .filter_map(|(k, v)| {
-            if k.starts_with(prefix) {
-                let k = k[prefix.len()..].to_owned();
-                Some((k, v))
+    /// Traits derived by the generated `BuildInfo` struct, in addition to
+    /// `serde::Serialize` with [`BuildDetails::serde_derive`]. Defaults to
+    /// `Debug`, `Clone`, `Copy`, `PartialEq`, and `Eq`; add `Default` to
+    /// derive it too, or give an empty list to derive nothing.
+    ///
+    /// Every field of the struct supports these, since [`BuildDetail::Cfg`]
+    /// is always left out of it. Types given to
+    /// [`BuildDetails::optional_wrapper`] need to support them as well.
+    pub fn struct_derives(&mut self, derives: &[&str]) -> &mut Self {
+        self.struct_derives = derives.iter().map(|x| (*x).to_owned()).collect();
+        self
+    }
+
+    /// Additionally generate a `build_info()` function returning a lazily
+    /// initialized `&'static BuildInfo`, shared by every caller.
+    ///
+    /// This generates the same `BuildInfo` struct and `BUILD_INFO` constant as
+    /// [`BuildDetails::serde_derive`], and uses `std::sync::OnceLock`, so no
+    /// extra runtime dependency is needed.
+    pub fn lazy_build_info(&mut self, enabled: bool) -> &mut Self {
+        self.lazy_build_info = enabled;
+        self
+    }
+
+    /// Additionally generate a `DETAIL_MANIFEST` constant, listing the name of
+    /// every generated constant and whether it was required.
+    ///
+    /// ```ignore
+    /// pub const DETAIL_MANIFEST: &'static [(&'static str, bool)] = &[
+    ///     ("VERSION", false),
+    ///     ("PROFILE", true),
+    /// ];
+    /// ```
+    pub fn detail_manifest(&mut self, enabled: bool) -> &mut Self {
+        self.detail_manifest = enabled;
+        self
+    }
+
+    /// Additionally generate a `const fn is_at_least(major, minor, patch)`,
+    /// returning whether the crate's version is at least the given one.
+    ///
+    /// The version is parsed from `CARGO_PKG_VERSION` while generating, so no
+    /// runtime dependency is needed. Pre-release and build metadata are
+    /// ignored, so `1.2.3-beta` counts as `1.2.3`.
+    ///
+    /// ```ignore
+    /// if build_details::is_at_least(1, 2, 0) {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn version_gate(&mut self, enabled: bool) -> &mut Self {
+        self.version_gate = enabled;
+        self
+    }
+
+    /// Additionally generate a `fn semver() -> &'static str`, returning the
+    /// crate's version with any pre-release and build metadata, like
+    /// `"1.2.3-beta+abc"`.
+    ///
+    /// The version is parsed from `CARGO_PKG_VERSION` while generating and
+    /// put back together from its parts, so it's a valid semver version even
+    /// when only the parts are otherwise generated.
+    pub fn with_semver_accessor(&mut self, enabled: bool) -> &mut Self {
+        self.semver_accessor = enabled;
+        self
+    }
+
+    /// Render optional details with a custom type instead of `Option`, for
+    /// crates with their own optionality types.
+    ///
+    /// The type of an optional constant becomes `type_path<T>`, a present
+    /// value is generated as `some_ctor(value)`, and a missing one as
+    /// `none_expr`. Both must be usable in a `const`. Takes precedence over
+    /// [`BuildDetails::placeholder_missing`].
+    ///
+    /// ```no_run
+    /// # use build_details::BuildDetails;
+    /// BuildDetails::default()
+    ///     .optional_wrapper("::my_crate::Maybe", "::my_crate::Maybe::some", "::my_crate::Maybe::none()")
+    ///     .generate("build_details.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn optional_wrapper(
+        &mut self,
+        type_path: &str,
+        some_ctor: &str,
+        none_expr: &str,
+    ) -> &mut Self {
+        self.optional_wrapper = Some(OptionalWrapper {
+            type_path: type_path.to_owned(),
+            some_ctor: some_ctor.to_owned(),
+            none_expr: none_expr.to_owned(),
+        });
+        self
+    }
+
+    /// Generate [`BuildDetail::Timestamp`] as an `i64` instead of a `u64`, for
+    /// interoperability with systems that expect signed timestamps.
+    pub fn timestamp_signed(&mut self, enabled: bool) -> &mut Self {
+        self.timestamp_signed = enabled;
+        self
+    }
+
+    /// Value of [`BuildDetail::TargetCpu`] when no `-C target-cpu` flag was
+    /// given. Defaults to `"default"`, matching `rustc`'s behaviour.
+    ///
+    /// With `None`, the detail is treated as missing instead.
+    pub fn target_cpu_fallback(&mut self, fallback: Option<&str>) -> &mut Self {
+        self.target_cpu_fallback = fallback.map(str::to_owned);
+        self
+    }
+
+    /// Value of [`BuildDetail::PanicStrategy`] when no `-C panic` flag was
+    /// given. Defaults to `"unwind"`, matching `rustc`'s behaviour.
+    ///
+    /// With `None`, the detail is treated as missing instead.
+    pub fn panic_strategy_fallback(&mut self, fallback: Option<&str>) -> &mut Self {
+        self.panic_strategy_fallback = fallback.map(str::to_owned);
+        self
+    }
+
+    /// Render optional string details that are missing as `Some(text)`
+    /// instead of `None`.
+    ///
+    /// This applies to every optional detail with a `&'static str` value.
+    /// Details of other types are still rendered as `None` when missing.
+    pub fn placeholder_missing(&mut self, text: &str) -> &mut Self {
+        self.placeholder = Some(text.to_owned());
+        self
+    }
+
+    /// Render optional details that are missing as `expr` instead of `None`,
+    /// like a sentinel constant from the crate using them.
+    ///
+    /// The type stays `Option<T>`, and `expr` must be usable in a `const` of
+    /// that type, so `Default::default()` won't do. Since it applies to
+    /// every missing optional detail, whatever its type, an expression of a
+    /// single type only suits configurations where every optional detail
+    /// has that type. [`BuildDetails::optional_wrapper`] and
+    /// [`BuildDetails::placeholder_missing`] take precedence.
+    pub fn none_as(&mut self, expr: &str) -> &mut Self {
+        self.none_as = Some(expr.to_owned());
+        self
+    }
+
+    /// Fail with [`Error::AlreadyGenerated`] when [`BuildDetails::generate`]
+    /// is called for the same path more than once in a single run of
+    /// `build.rs`.
+    ///
+    /// Generated paths are recorded in a sentinel file in `OUT_DIR`.
+    pub fn detect_duplicates(&mut self, enabled: bool) -> &mut Self {
+        self.detect_duplicates = enabled;
+        self
+    }
+
+    /// Check that the generated code parses as Rust before writing it, failing
+    /// with [`Error::InvalidGeneratedCode`] if it doesn't.
+    ///
+    /// Requires the `verify` feature, which adds a dependency on `syn`.
+    #[cfg(feature = "verify")]
+    pub fn verify(&mut self, enabled: bool) -> &mut Self {
+        self.verify = enabled;
+        self
+    }
+
+    /// Group the generated constants into modules by category, instead of
+    /// generating them all at the top level.
+    ///
+    /// The modules are:
+    ///
+    ///   * `build`, for details about the build environment;
+    ///   * `git`, for details from the `git` repository;
+    ///   * `pkg`, for details from the package's `Cargo.toml`; and
+    ///   * `toolchain`, for details about the compiler and its flags.
+    ///
+    /// For example, [`BuildDetail::Version`] is generated as `pkg::VERSION`.
+    pub fn namespaced(&mut self, enabled: bool) -> &mut Self {
+        self.namespaced = enabled;
+        self
+    }
+
+    /// Generate the details as associated constants of a unit struct named
+    /// `type_name`, instead of at the top level, like
+    /// `impl Build { pub const VERSION: &'static str = ...; }`.
+    ///
+    /// The struct is generated too, so the name must not be taken, including
+    /// by the `BuildInfo` struct. This takes precedence over
+    /// [`BuildDetails::namespaced`], and the constants stay `const` even with
+    /// [`BuildDetails::use_static`], since there are no associated statics.
+    /// Other generated items, like `BUILD_INFO`, stay at the top level.
+    pub fn impl_on(&mut self, type_name: &str) -> &mut Self {
+        self.impl_on = Some(type_name.to_owned());
+        self
+    }
+
+    /// Generate the constant for `detail` as `name`, instead of its usual
+    /// name, like `PKG_VERSION` instead of `VERSION`.
+    ///
+    /// Only the detail's main constant is renamed, so the `FEATURES_ENABLED`
+    /// of [`BuildDetail::FeatureFlags`] keeps its name. Generation fails with
+    /// [`Error::InvalidConstName`] if `name` isn't a valid identifier.
+    pub fn rename(&mut self, detail: BuildDetail, name: &str) -> &mut Self {
+        self.renames.insert(detail, name.to_owned());
+        self
+    }
+
+    /// Start the name of every constant generated for a [`BuildDetail`] with
+    /// `prefix`, like `BUILD_VERSION` for `"BUILD_"`, to keep them apart from
+    /// the crate's own constants.
+    ///
+    /// The prefix goes before any name set with [`BuildDetails::rename`].
+    /// Constants added with methods like [`BuildDetails::include_env_list`]
+    /// and generated items like `GENERATED_FROM` keep their names.
+    pub fn prefix(&mut self, prefix: &str) -> &mut Self {
+        self.prefix = Some(prefix.to_owned());
+        self
+    }
+
+    /// The name set for a constant called `name` with
+    /// [`BuildDetails::rename`] and [`BuildDetails::prefix`], if any, where
+    /// `detail` is `None` for the companions of a detail.
+    fn renamed(&self, detail: Option<BuildDetail>, name: &str) -> Option<String> {
+        let renamed = detail.and_then(|x| self.renames.get(&x));
+
+        if renamed.is_none() && self.prefix.is_none() {
+            return None;
+        }
+
+        Some(format!(
+            "{}{}",
+            self.prefix.as_deref().unwrap_or(""),
+            renamed.map_or(name, String::as_str)
+        ))
+    }
+
+    /// The constant to generate for `detail`, with any transform applied.
+    fn constant(&self, detail: BuildDetail) -> Box<dyn Constant> {
+        let constant = detail.into_render(self);
+
+        let transform = self.transforms.get(&detail);
+        let max_len = self.truncations.get(&detail);
+
+        if constant.value_type() != STR_TYPE || (transform.is_none() && max_len.is_none()) {
+            return constant;
+        }
+
+        let mut value = constant.resolve();
+
+        if let Some(transform) = transform {
+            value = value.map(|x| (transform.0)(&x));
+        }
+
+        if let Some(max_len) = max_len {
+            value = value.map(|x| truncate(x, *max_len));
+        }
+
+        Box::from(BuildEnv::with_value(constant.name().to_owned(), value))
+    }
+
+    fn module_of(&self, detail: BuildDetail) -> Option<&'static str> {
+        if self.namespaced {
+            Some(detail.category())
+        } else {
+            None
+        }
+    }
+
+    /// Transform the value of a string [`BuildDetail`] with `f` before it is
+    /// generated.
+    ///
+    /// The transform runs in `build.rs`, while generating, and the result is
+    /// generated as a literal. Details that would otherwise be read with
+    /// `env!`, like [`BuildDetail::Version`], are read from the environment of
+    /// `build.rs` instead. Details that aren't strings are not affected.
+    ///
+    /// ```no_run
+    /// # use build_details::{BuildDetail, BuildDetails};
+    /// BuildDetails::default()
+    ///     .map_value(BuildDetail::Profile, |x| x.to_uppercase())
+    ///     .generate("build_details.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn map_value<F>(&mut self, detail: BuildDetail, f: F) -> &mut Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.transforms.insert(detail, Transform(Arc::new(f)));
+        self
+    }
+
+    /// Format the value of a string [`BuildDetail`] with the function `f`
+    /// before it is generated, like [`BuildDetails::map_value`] but with a
+    /// plain function that can be shared and tested on its own.
+    ///
+    /// The [`formatters`] module has a few built-in ones:
+    ///
+    ///   * [`formatters::truncate_40`] shortens the value to 40 bytes;
+    ///   * [`formatters::basename`] keeps the last component of a path; and
+    ///   * [`formatters::https_only`] upgrades `http://` URLs to `https://`.
+    ///
+    /// A detail has at most one formatter or transform, whichever was set
+    /// last.
+    ///
+    /// ```no_run
+    /// # use build_details::{formatters, BuildDetail, BuildDetails};
+    /// BuildDetails::default()
+    ///     .include(BuildDetail::Homepage)
+    ///     .formatter(BuildDetail::Homepage, formatters::https_only)
+    ///     .generate("build_details.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn formatter(&mut self, detail: BuildDetail, f: fn(&str) -> String) -> &mut Self {
+        self.map_value(detail, f)
+    }
+
+    /// Rewrite the complete generated code with `f` before it is written, for
+    /// example to add a license header or run a formatter.
+    ///
+    /// `f` runs after everything has been rendered, and an error it returns
+    /// fails generation. The rewritten code is what
+    /// [`BuildDetails::max_output_bytes`] measures, what verification checks
+    /// when the `verify` feature is enabled, and what
+    /// [`BuildDetails::generate_region`] splices in before atomically
+    /// replacing the file.
+    ///
+    /// ```no_run
+    /// # use build_details::BuildDetails;
+    /// BuildDetails::default()
+    ///     .post_process(|code| Ok(format!("// @generated\n{}", code)))
+    ///     .generate("build_details.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn post_process<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(String) -> Result<String> + Send + Sync + 'static,
+    {
+        self.post_process = Some(PostProcess(Arc::new(f)));
+        self
+    }
+
+    /// Shorten the value of a string [`BuildDetail`] to at most `max_len`
+    /// bytes, ending with `...` when anything was cut, to keep details like
+    /// [`BuildDetail::RustFlags`] from bloating the binary.
+    ///
+    /// Truncation is lossy, and off by default. Like
+    /// [`BuildDetails::map_value`], the value is determined in `build.rs` and
+    /// generated as a literal, and applies after any transform. The value is
+    /// only cut between characters, so it may end up a little shorter.
+    pub fn truncate(&mut self, detail: BuildDetail, max_len: usize) -> &mut Self {
+        self.truncations.insert(detail, max_len);
+        self
+    }
+
+    /// Include an optional constant named `const_name`, holding the value of
+    /// the environment variable `env_var` split on `sep`.
+    ///
+    /// The variable is read while generating, and generated as a
+    /// `&'static [&'static str]`. An empty variable gives an empty slice, and
+    /// an unset variable gives `None`.
+    pub fn include_env_list(&mut self, const_name: &str, env_var: &str, sep: char) -> &mut Self {
+        self.extras.push(Extra::EnvList {
+            name: const_name.to_owned(),
+            var: env_var.to_owned(),
+            sep,
+        });
+        self
+    }
+
+    /// Include a constant named `const_name`, holding `entries` as a
+    /// `phf::Map<&'static str, &'static str>`, like [`BuildDetail::Cfg`].
+    ///
+    /// When a key appears more than once, the last value is kept. The map is
+    /// affected by [`BuildDetails::phf_path`] and
+    /// [`BuildDetails::ordered_cfg`].
+    pub fn include_map(&mut self, const_name: &str, entries: &[(&str, &str)]) -> &mut Self {
+        self.extras.push(Extra::Map {
+            name: const_name.to_owned(),
+            entries: entries
+                .iter()
+                .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+                .collect(),
+        });
+        self
+    }
+
+    /// Include a `&'static str` constant named `const_name`, holding the
+    /// version, abbreviated `HEAD` commit, and build date, like
+    /// `"1.2.0 (3f2a1bc 2017-07-14)"`, to pass to `clap`:
+    ///
+    /// ```ignore
+    /// Command::new("my-tool").version(build_details::VERSION_STRING)
+    /// ```
+    ///
+    /// The commit is left out when the build isn't inside a repository, and
+    /// the date is in UTC, honouring `SOURCE_DATE_EPOCH`.
+    pub fn clap_version_string(&mut self, const_name: &str) -> &mut Self {
+        self.extras.push(Extra::ClapVersion(const_name.to_owned()));
+        self
+    }
+
+    /// Include an optional `PREV_BINARY_SIZE: u64` constant, holding the size
+    /// in bytes of the file at `binary`, for tracking how the size of a binary
+    /// changes between builds.
+    ///
+    /// The file is read while generating, which is before the binary being
+    /// built exists, so the value is the size of the *previous* build, and
+    /// `None` if there wasn't one. No `cargo:rerun-if-changed` directive is
+    /// printed, since every build would change the file and rerun the build
+    /// script, so the value only updates when the build script reruns for
+    /// other reasons.
+    pub fn track_binary_size(&mut self, binary: PathBuf) -> &mut Self {
+        self.extras.push(Extra::BinarySize(binary));
+        self
+    }
+
+    /// Include a `BUILD_NUMBER: u64` constant, read from `counter_file` and
+    /// incremented every time code is generated.
+    ///
+    /// This gives a build counter that persists across local builds, without
+    /// relying on CI. The file is created holding `1` when it doesn't exist,
+    /// and is safe to share between concurrent builds.
+    pub fn auto_build_number(&mut self, counter_file: PathBuf) -> &mut Self {
+        self.extras.push(Extra::BuildNumber(counter_file));
+        self
+    }
+
+    /// Include an optional `&'static str` constant named `const_name`, holding
+    /// the contents of the file at `path`, for baking in small files like a
+    /// banner or license.
+    ///
+    /// The file is read while generating, and `None` is generated when it
+    /// doesn't exist. A file that isn't UTF-8 is an error; use
+    /// [`BuildDetails::include_file_bytes`] for binary files.
+    /// [`BuildDetails::generate`] prints a `cargo:rerun-if-changed` directive
+    /// for the file, which stops Cargo from rerunning the build script
+    /// whenever any other file in the package changes.
+    pub fn include_file(&mut self, const_name: &str, path: PathBuf) -> &mut Self {
+        self.extras.push(Extra::File {
+            name: const_name.to_owned(),
+            path,
+            binary: false,
+        });
+        self
+    }
+
+    /// Like [`BuildDetails::include_file`], but generates the contents as a
+    /// `&'static [u8]`.
+    pub fn include_file_bytes(&mut self, const_name: &str, path: PathBuf) -> &mut Self {
+        self.extras.push(Extra::File {
+            name: const_name.to_owned(),
+            path,
+            binary: true,
+        });
+        self
+    }
+
+    /// Creates a file called `path` in the build's `OUT_DIR` directory, or in
+    /// the directory set with [`BuildDetails::output_dir`]. See the crate
+    /// documentation for an example.
+    ///
+    /// Fails with [`Error::OutDirNotWritable`] when files can't be created in
+    /// the directory.
+    pub fn generate<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let out_dir = match (&self.output_dir, env::var_os("OUT_DIR")) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(x)) => PathBuf::from(x),
+            (None, None) => return Err(Error::MissingEnv("OUT_DIR")),
+        };
+
+        check_writable(&out_dir)?;
+
+        let generated_from = if self.with_source_path {
+            Some(path.as_ref().to_string_lossy().into_owned())
+        } else {
+            None
+        };
+
+        let out_path = match self.target_label {
+            Some(ref label) => out_dir.join(label_path(path.as_ref(), label)),
+            None => out_dir.join(path),
+        };
+
+        if self.detect_duplicates {
+            record_generated(&out_dir, &out_path)?;
+        }
+
+        let mut out_file = File::create(out_path)?;
+
+        let missing = self.write_checked(&mut out_file, generated_from.as_ref())?;
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        for name in missing {
+            writeln!(
+                stdout,
+                "cargo:warning=optional build detail {} is missing",
+                name
+            )?;
+        }
+
+        self.write_directives(&mut stdout)
+    }
+
+    /// The `cargo:` directives (without the prefix) needed by the selected
+    /// details and extras, sorted and without duplicates.
+    fn directives(&self) -> Vec<String> {
+        let docs_safe = self.docs_safe();
+
+        let mut directives: Vec<_> = self
+            .selected()
+            .filter(|(detail, _)| !docs_safe || detail.placeholder(self).is_none())
+            .flat_map(|(detail, _)| detail.directives())
+            .chain(self.extras.iter().flat_map(Extra::directives))
+            .collect();
+
+        let replaceable = self
+            .selected()
+            .any(|(detail, _)| detail.placeholder(self).is_some());
+
+        if self.docs_rs_safe.is_none() && replaceable && env::var_os("DOCS_RS").is_some() {
+            directives.push("rerun-if-env-changed=DOCS_RS".to_owned());
+        }
+
+        directives.sort();
+        directives.dedup();
+        directives
+    }
+
+    /// Writes the `cargo:` directives needed by the selected details.
+    fn write_directives(&self, out: &mut dyn Write) -> Result<()> {
+        for directive in self.directives() {
+            writeln!(out, "cargo:{}", directive)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the generated code to standard output, to inspect what a
+    /// configuration produces.
+    ///
+    /// When called from `build.rs`, Cargo captures the output in the `output`
+    /// file next to `OUT_DIR`, and shows it with `cargo build -vv`. Every
+    /// generated line is Rust code starting with an item, an attribute, a
+    /// closing brace, or indentation, so none of them can be mistaken for a
+    /// `cargo:` directive.
+    pub fn dump(&self) -> Result<()> {
+        let stdout = io::stdout();
+        let mut lock = stdout.lock();
+
+        self.write_to(&mut lock)
+    }
+
+    /// Calls `f` with the name and value of every constant that would be
+    /// generated, for logging or forwarding build metadata elsewhere.
+    ///
+    /// Values are determined the same way as while generating, and are `None`
+    /// when missing, or when they can't be represented as text, like
+    /// [`BuildDetail::Cfg`]. Nothing is generated, although
+    /// [`BuildDetails::auto_build_number`] still increments its counter.
+    ///
+    /// ```no_run
+    /// # use build_details::BuildDetails;
+    /// BuildDetails::default()
+    ///     .inspect(|name, value| println!("{} = {:?}", name, value))
+    ///     .unwrap();
+    /// ```
+    pub fn inspect<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&str, Option<&str>),
+    {
+        for entry in self.entries()? {
+            let value = entry.constant.resolve();
+            f(entry.constant.name(), value.as_deref());
+        }
+
+        Ok(())
+    }
+
+    /// Writes the generated code to a [`::std::io::Write'] instead of to a file.
+    pub fn write_to(&self, out_file: &mut dyn Write) -> Result<()> {
+        self.write_checked(out_file, None).map(|_| ())
+    }
+
+    /// Like [`BuildDetails::write_to`], also emitting `GENERATED_FROM` when
+    /// `generated_from` is given, and returning the names of the optional
+    /// constants to warn about with [`BuildDetails::warn_on_missing`].
+    fn write_checked(
+        &self,
+        out_file: &mut dyn Write,
+        generated_from: Option<&String>,
+    ) -> Result<Vec<String>> {
+        let mut source = vec![];
+        let missing = self.write_source(&mut source, generated_from)?;
+
+        if let Some(ref post_process) = self.post_process {
+            let text = String::from_utf8(source).expect("generated code is UTF-8");
+            source = (post_process.0)(text)?.into_bytes();
+        }
+
+        if let Some(max) = self.max_output_bytes {
+            if source.len() > max {
+                return Err(Error::OutputTooLarge(source.len()));
+            }
+        }
+
+        #[cfg(feature = "verify")]
+        {
+            if self.verify {
+                verify::verify(&source)?;
+            }
+        }
+
+        out_file.write_all(&source)?;
+
+        Ok(missing)
+    }
+
+    /// Every constant to generate, in order.
+    fn entries(&self) -> Result<Vec<Entry>> {
+        if self.strict_conflicts {
+            let conflict = self.conflicts.iter().min_by_key(|x| format!("{:?}", x));
+
+            if let Some(detail) = conflict {
+                return Err(Error::ConflictingRequirement(*detail));
+            }
+        }
+
+        manifest::clear_cache();
+
+        let docs_safe = self.docs_safe();
+
+        let timed = self.selected().any(|(detail, _)| {
+            matches!(
+                detail,
+                BuildDetail::Timestamp | BuildDetail::BuildDateLocal | BuildDetail::BuildTzOffset
+            )
+        });
+
+        if timed && !docs_safe {
+            source_date_epoch()?;
+        }
+
+        let mut entries = vec![];
+
+        for (detail, required) in self.selected() {
+            let placeholder = detail.placeholder(self).filter(|_| docs_safe);
+
+            let mut constant = match placeholder {
+                Some((name, value_type)) => docs_placeholder(name, value_type, required),
+                None => self.constant(detail),
+            };
+
+            if let Some(name) = self.renamed(Some(detail), constant.name()) {
+                check_const_name(&name)?;
+                constant.rename(name);
+            }
+
+            entries.push(Entry {
+                constant,
+                optional: !required,
+                module: self.module_of(detail),
+                flag_bit: detail.flag_bit(),
+            });
+
+            for mut constant in detail.companions() {
+                if let Some(name) = self.renamed(None, constant.name()) {
+                    check_const_name(&name)?;
+                    constant.rename(name);
+                }
+
+                entries.push(Entry {
+                    constant,
+                    optional: !required,
+                    module: self.module_of(detail),
+                    flag_bit: None,
+                });
+            }
+        }
+
+        for extra in &self.extras {
+            entries.push(Entry {
+                constant: extra.constant(self)?,
+                optional: extra.optional(),
+                module: None,
+                flag_bit: None,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn write_source(
+        &self,
+        out_file: &mut dyn Write,
+        generated_from: Option<&String>,
+    ) -> Result<Vec<String>> {
+        let started = Instant::now();
+        let mut items = vec![];
+        let mut fields = vec![];
+        let mut manifest = vec![];
+        let mut missing = vec![];
+        let mut flags = vec![];
+        let mut deferred = false;
+        let mut declarations = vec![];
+        let mut imports = HashSet::new();
+        let impl_on = self.impl_on.as_deref();
+
+        for entry in self.entries()? {
+            let constant = entry.constant;
+
+            if let Some(bit) = entry.flag_bit {
+                let set = constant.resolve().is_some_and(|x| x == "true");
+                flags.push((constant.name().to_owned(), bit, set));
+            }
+
+            if self.warn_on_missing && entry.optional && constant.missing() {
+                missing.push(constant.name().to_owned());
+            }
+
+            let defer = self.defer_missing && !entry.optional && constant.missing();
+            let nested = entry.module.is_some() && impl_on.is_none();
+
+            let line = if defer {
+                deferred = true;
+                render_deferred(constant.name(), nested)
+            } else if entry.optional {
+                match (&self.optional_wrapper, &self.placeholder) {
+                    (Some(wrapper), _) => constant.render_wrapped(wrapper)?,
+                    (None, Some(text)) if constant.value_type() == STR_TYPE => {
+                        constant.render_placeholder(text)?
+                    }
+                    _ => match self.none_as {
+                        Some(ref expr) if constant.missing() => format!(
+                            "pub const {}: Option<{}> = {};",
+                            constant.name(),
+                            constant.value_type(),
+                            expr
+                        ),
+                        _ => constant.render_option()?,
+                    },
+                }
+            } else {
+                constant.render()?
+            };
+
+            let wrapped = entry.optional && self.optional_wrapper.is_some();
+            let cow = self.as_cow && !wrapped && !defer && constant.value_type() == STR_TYPE;
+
+            let line = if cow { borrow_cow(&line) } else { line };
+
+            // Declarations are written once, at the top level, so modules
+            // import them.
+            if let Some(declaration) = constant.declaration() {
+                if !declarations.contains(&declaration) {
+                    declarations.push(declaration);
+                }
+
+                if nested && imports.insert((entry.module, constant.value_type().to_owned())) {
+                    let import = format!("use super::{};", constant.value_type());
+                    items.push((entry.module, import));
+                }
+            }
+
+            let line = match self.list_format {
+                ListFormat::SingleLine if constant.value_type() == LIST_TYPE => {
+                    single_line_list(&line)
+                }
+                _ => line,
+            };
+
+            // Every constant renders as `pub const NAME: ...`.
+            let line = if self.use_static && impl_on.is_none() {
+                line.replacen("pub const ", "pub static ", 1)
             } else {
-                None
+                line
+            };
+
+            let own_type = if cow { COW_TYPE } else { constant.value_type() };
+
+            let value_type = match (entry.optional, &self.optional_wrapper) {
+                (true, Some(wrapper)) => wrapper.ty(own_type),
+                (true, None) => format!("Option<{}>", own_type),
+                (false, _) => own_type.to_owned(),
+            };
+
+            let newtype = self.newtypes && !wrapped && !cow && constant.value_type() == STR_TYPE;
+
+            manifest.push((constant.name().to_owned(), !entry.optional));
+            items.push((entry.module, line));
+
+            if self.value_checksums && !defer && constant.value_type() == STR_TYPE {
+                let crc = constant.resolve().map(|x| crc32::checksum(x.as_bytes()));
+                items.push((
+                    entry.module,
+                    render_checksum(constant.name(), crc, entry.optional)?,
+                ));
+            }
+
+            if constant.plain() && !defer {
+                let mut field = Field::new(&*constant, value_type, impl_on.or(entry.module));
+
+                if newtype {
+                    field.wrap(entry.optional);
+                }
+
+                fields.push(field);
+            }
+        }
+
+        if deferred {
+            writeln!(out_file, "#[derive(Clone, Copy)]")?;
+            writeln!(out_file, "pub struct MissingBuildDetail;")?;
+        }
+
+        for declaration in declarations {
+            writeln!(out_file, "{}", declaration)?;
+        }
+
+        if let Some(type_name) = impl_on {
+            write_impl(out_file, type_name, &items)?;
+        } else if self.namespaced {
+            write_modules(out_file, &items)?;
+        } else {
+            for (_, line) in items {
+                writeln!(out_file, "{}", line)?;
+            }
+        }
+
+        if self.build_flags {
+            write_build_flags(out_file, &mut flags)?;
+        }
+
+        if let Some(path) = generated_from {
+            writeln!(
+                out_file,
+                "pub const GENERATED_FROM: &'static str = {:?};",
+                path
+            )?;
+        }
+
+        if let Some(ref label) = self.target_label {
+            writeln!(
+                out_file,
+                "pub const TARGET_LABEL: &'static str = {:?};",
+                label
+            )?;
+        }
+
+        if self.gen_timing {
+            writeln!(
+                out_file,
+                "pub const BUILD_DETAILS_GEN_MS: u64 = {}u64;",
+                started.elapsed().as_millis()
+            )?;
+        }
+
+        if self.detail_manifest {
+            write_detail_manifest(out_file, &manifest)?;
+        }
+
+        if self.release_requires_git() {
+            let module = impl_on.or(self.module_of(BuildDetail::GitCommitShort));
+            let name = self
+                .renamed(Some(BuildDetail::GitCommitShort), "GIT_COMMIT_SHORT")
+                .unwrap_or_else(|| "GIT_COMMIT_SHORT".to_owned());
+            write_git_assertion(out_file, module, &name)?;
+        }
+
+        if self.version_gate {
+            write_version_gate(out_file)?;
+        }
+
+        if self.semver_accessor {
+            write_semver_accessor(out_file)?;
+        }
+
+        if self.serde_derive || self.lazy_build_info {
+            write_struct(out_file, &fields, &self.struct_derives, self.serde_derive)?;
+        }
+
+        if self.lazy_build_info {
+            write_lazy_build_info(out_file)?;
+        }
+
+        Ok(missing)
+    }
+}
+
+/// Puts a rendered list constant on one line, for [`ListFormat::SingleLine`].
+fn single_line_list(line: &str) -> String {
+    // Lists render one item per line, and items are escaped, so the only
+    // line breaks are the ones between items.
+    line.replace("&[\n]", "&[]")
+        .replace("&[\n    ", "&[")
+        .replace(",\n    ", ", ")
+        .replace(",\n]", "]")
+}
+
+/// Turns a rendered string constant, optional or not, into one of type
+/// `Cow<'static, str>`, for [`BuildDetails::as_cow`].
+fn borrow_cow(line: &str) -> String {
+    // Rendered as `pub const NAME: TYPE = VALUE;`, and the type has no ` = `.
+    let split = line.find(" = ").expect("constant has a value");
+    let (head, value) = (&line[..split], &line[split + 3..line.len() - 1]);
+    let head = head.replacen(STR_TYPE, COW_TYPE, 1);
+
+    let value = match value.strip_prefix("Some(") {
+        Some(inner) => format!("Some(::std::borrow::Cow::Borrowed({})", inner),
+        None if value == "None" => value.to_owned(),
+        // Like `option_env!("NAME")`.
+        None if head.contains("Option<") => format!(
+            "match {} {{ Some(x) => Some(::std::borrow::Cow::Borrowed(x)), None => None }}",
+            value
+        ),
+        None => format!("::std::borrow::Cow::Borrowed({})", value),
+    };
+
+    format!("{} = {};", head, value)
+}
+
+/// Renders the `NAME_CRC` constant of [`BuildDetails::with_value_checksums`].
+fn render_checksum(name: &str, crc: Option<u32>, optional: bool) -> Result<String> {
+    let detail = Detail {
+        name: format!("{}_CRC", name).into(),
+        value_type: "u32".into(),
+        value: crc,
+    };
+
+    if optional {
+        detail.render_option()
+    } else {
+        detail.render()
+    }
+}
+
+/// Renders the constant generated for a missing required detail with
+/// [`BuildDetails::defer_missing`], inside a module when `nested`.
+fn render_deferred(name: &str, nested: bool) -> String {
+    let path = if nested { "super::" } else { "" };
+
+    format!(
+        "#[deprecated(note = \"build detail {} was missing while generating\")]\n\
+         pub const {}: {}MissingBuildDetail = {}MissingBuildDetail;",
+        name, name, path, path
+    )
+}
+
+/// Writes the `BUILD_FLAGS` constant and its masks, from each boolean
+/// constant's name, bit, and value.
+fn write_build_flags(out: &mut dyn Write, flags: &mut [(String, u32, bool)]) -> Result<()> {
+    flags.sort_by_key(|(_, bit, _)| *bit);
+
+    let mut value = 0u32;
+
+    for (name, bit, set) in flags.iter() {
+        writeln!(out, "pub const BUILD_FLAG_{}: u32 = 1u32 << {};", name, bit)?;
+
+        if *set {
+            value |= 1 << bit;
+        }
+    }
+
+    writeln!(out, "pub const BUILD_FLAGS: u32 = {}u32;", value)?;
+
+    Ok(())
+}
+
+/// Writes the `DETAIL_MANIFEST` constant, from each constant's name and
+/// whether it is required.
+fn write_detail_manifest(out: &mut dyn Write, manifest: &[(String, bool)]) -> Result<()> {
+    writeln!(
+        out,
+        "pub const DETAIL_MANIFEST: &'static [(&'static str, bool)] = &["
+    )?;
+
+    for (name, required) in manifest {
+        writeln!(out, "    ({:?}, {}),", name, required)?;
+    }
+
+    writeln!(out, "];")?;
+
+    Ok(())
+}
+
+/// Writes a function that only compiles when the `GIT_COMMIT_SHORT`
+/// constant, generated as `name`, is a plain string, for [`BuildDetails::assert_git_in_release`].
+fn write_git_assertion(out: &mut dyn Write, module: Option<&str>, name: &str) -> Result<()> {
+    let path = match module {
+        Some(module) => format!("{}::{}", module, name),
+        None => name.to_owned(),
+    };
+
+    writeln!(out, "#[allow(dead_code)]")?;
+    writeln!(out, "fn __assert_git_in_release() -> &'static str {{")?;
+    writeln!(out, "    {}", path)?;
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
+
+/// Writes the `is_at_least` function, comparing against the crate's version.
+fn write_version_gate(out: &mut dyn Write) -> Result<()> {
+    let (major, minor, patch) = env::var("CARGO_PKG_VERSION")
+        .ok()
+        .and_then(|x| version::parts(&x))
+        .ok_or(Error::MissingEnv("CARGO_PKG_VERSION"))?;
+
+    // Components that are zero make some comparisons trivially false.
+    writeln!(
+        out,
+        "#[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]"
+    )?;
+    writeln!(
+        out,
+        "pub const fn is_at_least(major: u64, minor: u64, patch: u64) -> bool {{"
+    )?;
+    writeln!(out, "    if major != {}u64 {{", major)?;
+    writeln!(out, "        return major < {}u64;", major)?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "    if minor != {}u64 {{", minor)?;
+    writeln!(out, "        return minor < {}u64;", minor)?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "    patch <= {}u64", patch)?;
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
+
+/// The constant `name` of type `value_type` generated instead of a detail
+/// with [`BuildDetails::docs_rs_safe`].
+fn docs_placeholder(
+    name: &'static str,
+    value_type: &'static str,
+    required: bool,
+) -> Box<dyn Constant> {
+    fn detail<T>(
+        name: &'static str,
+        value_type: &'static str,
+        value: Option<T>,
+    ) -> Box<dyn Constant>
+    where
+        T: Literal + 'static,
+    {
+        Box::from(Detail {
+            name: name.into(),
+            value_type: value_type.into(),
+            value,
+        })
+    }
+
+    match value_type {
+        STR_TYPE => Box::from(BuildEnv::with_value(
+            name,
+            Some(String::new()).filter(|_| required),
+        )),
+        LIST_TYPE => Box::from(Detail {
+            name: name.into(),
+            value_type: LIST_TYPE.into(),
+            value: Some(BuildEnvList(vec![])).filter(|_| required),
+        }),
+        "bool" => detail(name, value_type, Some(false).filter(|_| required)),
+        "u8" => detail(name, value_type, Some(0u8).filter(|_| required)),
+        "u32" => detail(name, value_type, Some(0u32).filter(|_| required)),
+        "u64" => detail(name, value_type, Some(0u64).filter(|_| required)),
+        "i32" => detail(name, value_type, Some(0i32).filter(|_| required)),
+        "i64" => detail(name, value_type, Some(0i64).filter(|_| required)),
+        _ => unreachable!("no placeholder for {}", value_type),
+    }
+}
+
+/// Writes the `semver` function, returning the crate's version.
+fn write_semver_accessor(out: &mut dyn Write) -> Result<()> {
+    let semver = env::var("CARGO_PKG_VERSION")
+        .ok()
+        .and_then(|x| version::semver(&x))
+        .ok_or(Error::MissingEnv("CARGO_PKG_VERSION"))?;
+
+    writeln!(out, "pub fn semver() -> &'static str {{")?;
+    writeln!(out, "    {:?}", semver)?;
+    writeln!(out, "}}")?;
+
+    Ok(())
+}
+
+/// A constant to generate, and where to generate it.
+struct Entry {
+    constant: Box<dyn Constant>,
+    optional: bool,
+    module: Option<&'static str>,
+    flag_bit: Option<u32>,
+}
+
+/// A constant added by the user, rather than from a [`BuildDetail`].
+#[derive(Debug, Clone)]
+enum Extra {
+    EnvList {
+        name: String,
+        var: String,
+        sep: char,
+    },
+    BuildNumber(PathBuf),
+    File {
+        name: String,
+        path: PathBuf,
+        binary: bool,
+    },
+    Map {
+        name: String,
+        entries: Vec<(String, String)>,
+    },
+    ClapVersion(String),
+    BinarySize(PathBuf),
+}
+
+impl Extra {
+    fn constant(&self, details: &BuildDetails) -> Result<Box<dyn Constant>> {
+        match self {
+            Extra::EnvList { name, var, sep } => {
+                check_const_name(name)?;
+
+                let value = env::var(var).ok().map(|x| {
+                    x.split(*sep)
+                        .filter(|x| !x.is_empty())
+                        .map(str::to_owned)
+                        .collect()
+                });
+
+                Ok(Box::from(Detail {
+                    name: name.clone().into(),
+                    value_type: LIST_TYPE.into(),
+                    value: value.map(BuildEnvList),
+                }))
+            }
+            Extra::BuildNumber(path) => Ok(Box::from(Detail {
+                name: "BUILD_NUMBER".into(),
+                value_type: "u64".into(),
+                value: Some(counter::increment(path)?),
+            })),
+            Extra::File { name, path, binary } => {
+                check_const_name(name)?;
+
+                let contents = match fs::read(path) {
+                    Ok(x) => Some(x),
+                    Err(ref e) if e.kind() == io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(e.into()),
+                };
+
+                if *binary {
+                    return Ok(Box::from(Detail {
+                        name: name.clone().into(),
+                        value_type: BYTES_TYPE.into(),
+                        value: BuildBytes(contents),
+                    }));
+                }
+
+                let text = match contents {
+                    Some(x) => Some(
+                        String::from_utf8(x)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                    ),
+                    None => None,
+                };
+
+                Ok(Box::from(BuildEnv::with_value(name.clone(), text)))
+            }
+            Extra::BinarySize(path) => {
+                let size = match fs::metadata(path) {
+                    Ok(x) => Some(x.len()),
+                    Err(ref e) if e.kind() == io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(e.into()),
+                };
+
+                Ok(Box::from(Detail {
+                    name: "PREV_BINARY_SIZE".into(),
+                    value_type: "u64".into(),
+                    value: size,
+                }))
+            }
+            Extra::ClapVersion(name) => {
+                check_const_name(name)?;
+
+                Ok(Box::from(BuildEnv::with_value(
+                    name.clone(),
+                    clap_version()?,
+                )))
+            }
+            Extra::Map { name, entries } => {
+                check_const_name(name)?;
+
+                Ok(Box::from(BuildEnvMap::with_entries(
+                    name.clone(),
+                    entries.clone(),
+                    &details.phf_path,
+                    details.ordered_cfg,
+                )))
+            }
+        }
+    }
+
+    fn optional(&self) -> bool {
+        match self {
+            Extra::EnvList { .. } | Extra::File { .. } | Extra::BinarySize(_) => true,
+            Extra::BuildNumber(_) | Extra::Map { .. } | Extra::ClapVersion(_) => false,
+        }
+    }
+
+    /// The `cargo:` directives (without the prefix) that keep this constant
+    /// up to date.
+    fn directives(&self) -> Vec<String> {
+        match self {
+            Extra::File { path, .. } => vec![format!("rerun-if-changed={}", path.display())],
+            Extra::EnvList { .. }
+            | Extra::BuildNumber(_)
+            | Extra::Map { .. }
+            | Extra::ClapVersion(_)
+            | Extra::BinarySize(_) => vec![],
+        }
+    }
+}
+
+/// Inserts `label` before the extension of the file name in `path`, for
+/// [`BuildDetails::with_target_label`].
+fn label_path(path: &Path, label: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, label, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, label),
+    };
+
+    path.with_file_name(name)
+}
+
+/// The version string generated by [`BuildDetails::clap_version_string`].
+fn clap_version() -> Result<Option<String>> {
+    let version = match env::var("CARGO_PKG_VERSION") {
+        Ok(version) => version,
+        Err(_) => return Ok(None),
+    };
+
+    let mut extra = vec![];
+    extra.extend(git::git(&["rev-parse", "--short", "HEAD"]));
+
+    if let Some(secs) = build_time()?.and_then(|x| i64::try_from(x).ok()) {
+        extra.push(date::format_rfc3339(secs, 0)[..10].to_owned());
+    }
+
+    if extra.is_empty() {
+        return Ok(Some(version));
+    }
+
+    Ok(Some(format!("{} ({})", version, extra.join(" "))))
+}
+
+/// Shortens `value` to at most `max_len` bytes, marking the cut with `...`.
+fn truncate(mut value: String, max_len: usize) -> String {
+    const MARKER: &str = "...";
+
+    if value.len() <= max_len {
+        return value;
+    }
+
+    let mut end = max_len.saturating_sub(MARKER.len());
+
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    value.truncate(end);
+    value.push_str(&MARKER[..max_len.min(MARKER.len())]);
+    value
+}
+
+/// Fails unless `name` is usable as the name of a generated constant.
+fn check_const_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+
+    let valid = match chars.next() {
+        Some(first) => {
+            (first.is_ascii_alphabetic() || first == '_')
+                && chars.all(|x| x.is_ascii_alphanumeric() || x == '_')
+                && name != "_"
+        }
+        None => false,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidConstName(name.to_owned()))
+    }
+}
+
+/// The type used for optional details, set with
+/// [`BuildDetails::optional_wrapper`].
+#[derive(Debug, Clone)]
+struct OptionalWrapper {
+    type_path: String,
+    some_ctor: String,
+    none_expr: String,
+}
+
+impl OptionalWrapper {
+    fn ty(&self, value_type: &str) -> String {
+        format!("{}<{}>", self.type_path, value_type)
+    }
+
+    fn some(&self, value: &str) -> String {
+        format!("{}({})", self.some_ctor, value)
+    }
+
+    fn none(&self) -> String {
+        self.none_expr.clone()
+    }
+}
+
+/// A function registered with [`BuildDetails::map_value`].
+#[derive(Clone)]
+struct Transform(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl fmt::Debug for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Transform")
+    }
+}
+
+/// A function registered with [`BuildDetails::post_process`].
+#[derive(Clone)]
+struct PostProcess(Arc<dyn Fn(String) -> Result<String> + Send + Sync>);
+
+impl fmt::Debug for PostProcess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PostProcess")
+    }
+}
+
+/// How lists are laid out in the generated code. See
+/// [`BuildDetails::list_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    /// One item per line:
+    ///
+    /// ```ignore
+    /// pub const AUTHORS: &'static [&'static str] = &[
+    ///     "Ann",
+    ///     "Bob",
+    /// ];
+    /// ```
+    MultiLine,
+
+    /// All items on one line, like
+    /// `pub const AUTHORS: &'static [&'static str] = &["Ann", "Bob"];`.
+    SingleLine,
+}
+
+/// The target being built, as described by Cargo to `build.rs`.
+#[derive(Debug, Clone)]
+pub struct Target {
+    triple: Option<String>,
+    os: Option<String>,
+    family: Option<String>,
+}
+
+impl Target {
+    fn from_env() -> Self {
+        Target {
+            triple: env::var("TARGET").ok(),
+            os: env::var("CARGO_CFG_TARGET_OS").ok(),
+            family: env::var("CARGO_CFG_TARGET_FAMILY").ok(),
+        }
+    }
+
+    /// The target triple, from the `TARGET` environment variable.
+    pub fn triple(&self) -> Option<&str> {
+        self.triple.as_deref()
+    }
+
+    /// The target operating system, like `"linux"` or `"windows"`.
+    pub fn os(&self) -> Option<&str> {
+        self.os.as_deref()
+    }
+
+    /// Whether the target belongs to `family`, like `"unix"` or `"wasm"`.
+    pub fn has_family(&self, family: &str) -> bool {
+        match self.family {
+            Some(ref x) => x.split(',').any(|x| x == family),
+            None => false,
+        }
+    }
+}
+
+/// Fails with [`Error::OutDirNotWritable`] if a file can't be created in `dir`
+/// for lack of permission, so that failure is reported clearly.
+fn check_writable(dir: &Path) -> Result<()> {
+    let probe = dir.join(format!(".build_details_probe.{}", process::id()));
+
+    match File::create(&probe) {
+        Ok(_) => fs::remove_file(&probe).map_err(Error::from),
+        Err(ref e)
+            if e.kind() == io::ErrorKind::PermissionDenied
+                || e.kind() == io::ErrorKind::ReadOnlyFilesystem =>
+        {
+            Err(Error::OutDirNotWritable(dir.to_path_buf()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Name of the file, in `OUT_DIR`, listing the paths generated so far.
+const SENTINEL: &str = ".build_details_generated";
+
+/// Records `out_path` in the sentinel file, failing if it was already generated
+/// by this process.
+///
+/// The first line of the sentinel is the id of the process that wrote it, so
+/// entries left over from previous runs of `build.rs` are discarded.
+fn record_generated(out_dir: &Path, out_path: &Path) -> Result<()> {
+    let sentinel = out_dir.join(SENTINEL);
+    let run = process::id().to_string();
+    let path = out_path.to_string_lossy();
+
+    let mut generated = match fs::read_to_string(&sentinel) {
+        Ok(contents) => {
+            let mut lines = contents.lines();
+
+            if lines.next() == Some(run.as_str()) {
+                lines.map(str::to_owned).collect()
+            } else {
+                vec![]
+            }
+        }
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(e.into()),
+    };
+
+    if generated.iter().any(|x| *x == path) {
+        return Err(Error::AlreadyGenerated(out_path.to_path_buf()));
+    }
+
+    generated.insert(0, run);
+    generated.push(path.into_owned());
+
+    let mut file = File::create(sentinel)?;
+    for line in generated {
+        writeln!(file, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+/// A member of the generated `BuildInfo` struct.
+struct Field {
+    name: String,
+    path: String,
+    value_type: String,
+    newtype: Option<String>,
+}
+
+impl Field {
+    fn new(constant: &dyn Constant, value_type: String, module: Option<&str>) -> Self {
+        let path = match module {
+            Some(module) => format!("{}::{}", module, constant.name()),
+            None => constant.name().to_owned(),
+        };
+
+        Field {
+            name: constant.name().to_owned(),
+            path,
+            value_type,
+            newtype: None,
+        }
+    }
+
+    /// Wraps the string value of this field in a newtype named after it, like
+    /// `Version` for `VERSION`.
+    fn wrap(&mut self, optional: bool) {
+        let newtype: String = self
+            .name
+            .split('_')
+            .filter(|x| !x.is_empty())
+            .map(|word| {
+                let mut chars = word.chars();
+                let first = chars.next().into_iter().flat_map(char::to_uppercase);
+                first
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect::<String>()
+            })
+            .collect();
+
+        if optional {
+            self.value_type = format!("Option<{}>", newtype);
+            self.path = format!(
+                "match {} {{ Some(x) => Some({}(x)), None => None }}",
+                self.path, newtype
+            );
+        } else {
+            self.value_type = newtype.clone();
+            self.path = format!("{}({})", newtype, self.path);
+        }
+
+        self.newtype = Some(newtype);
+    }
+}
+
+/// Writes each `(module, item)` pair inside a `pub mod` with that name, or at
+/// the top level when there is no module.
+fn write_modules(out_file: &mut dyn Write, items: &[(Option<&str>, String)]) -> Result<()> {
+    let mut modules = BTreeMap::new();
+
+    for (module, item) in items {
+        match module {
+            Some(module) => modules.entry(module).or_insert_with(Vec::new).push(item),
+            None => writeln!(out_file, "{}", item)?,
+        }
+    }
+
+    for (module, items) in modules {
+        writeln!(out_file, "pub mod {} {{", module)?;
+
+        for item in items {
+            for line in item.lines() {
+                writeln!(out_file, "    {}", line)?;
+            }
+        }
+
+        writeln!(out_file, "}}")?;
+    }
+
+    Ok(())
+}
+
+/// Writes `items` as associated constants of a unit struct named
+/// `type_name`, for [`BuildDetails::impl_on`].
+fn write_impl(
+    out_file: &mut dyn Write,
+    type_name: &str,
+    items: &[(Option<&str>, String)],
+) -> Result<()> {
+    writeln!(out_file, "pub struct {};", type_name)?;
+    writeln!(out_file, "impl {} {{", type_name)?;
+
+    for (_, item) in items {
+        for line in item.lines() {
+            writeln!(out_file, "    {}", line)?;
+        }
+    }
+
+    writeln!(out_file, "}}")?;
+
+    Ok(())
+}
+
+fn write_struct(
+    out_file: &mut dyn Write,
+    fields: &[Field],
+    derives: &[String],
+    serde: bool,
+) -> Result<()> {
+    let write_derives = |out_file: &mut dyn Write| -> Result<()> {
+        if !derives.is_empty() {
+            writeln!(out_file, "#[derive({})]", derives.join(", "))?;
+        }
+
+        if serde {
+            writeln!(out_file, "#[derive(::serde::Serialize)]")?;
+        }
+
+        Ok(())
+    };
+
+    for newtype in fields.iter().filter_map(|x| x.newtype.as_ref()) {
+        write_derives(out_file)?;
+        writeln!(out_file, "pub struct {}(pub &'static str);", newtype)?;
+    }
+
+    write_derives(out_file)?;
+    writeln!(out_file, "pub struct BuildInfo {{")?;
+
+    for field in fields {
+        writeln!(
+            out_file,
+            "    pub {}: {},",
+            field.name.to_lowercase(),
+            field.value_type
+        )?;
+    }
+
+    writeln!(out_file, "}}")?;
+    writeln!(out_file, "pub const BUILD_INFO: BuildInfo = BuildInfo {{")?;
+
+    for field in fields {
+        writeln!(
+            out_file,
+            "    {}: {},",
+            field.name.to_lowercase(),
+            field.path
+        )?;
+    }
+
+    writeln!(out_file, "}};")?;
+
+    Ok(())
+}
+
+fn write_lazy_build_info(out_file: &mut dyn Write) -> Result<()> {
+    writeln!(out_file, "pub fn build_info() -> &'static BuildInfo {{")?;
+    writeln!(
+        out_file,
+        "    static CELL: ::std::sync::OnceLock<BuildInfo> = ::std::sync::OnceLock::new();"
+    )?;
+    writeln!(out_file, "    CELL.get_or_init(|| BUILD_INFO)")?;
+    writeln!(out_file, "}}")?;
+
+    Ok(())
+}
+
+/// List of build details that can be included in the generated code.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(Deserialize))]
+#[allow(clippy::manual_non_exhaustive)]
+pub enum BuildDetail {
+    /// Number of seconds since [`::std::time::UNIX_EPOCH`]
+    ///
+    /// When the `SOURCE_DATE_EPOCH` environment variable is set, it is used
+    /// instead of the current time, for reproducible builds. Generation fails
+    /// with [`Error::InvalidSourceDateEpoch`] when it isn't a number of
+    /// seconds. While it's set, [`BuildDetails::generate`] prints a
+    /// `cargo:rerun-if-env-changed` directive for it, as it does for the other
+    /// details using the build's time, so changing it regenerates the
+    /// details. Without it, no directive is printed, so Cargo keeps rerunning
+    /// the build script whenever any file in the package changes.
+    Timestamp,
+
+    /// Equivalent to the `CARGO_PKG_VERSION` environment variable.
+    Version,
+
+    /// The build metadata of the package version, after the `+` (like
+    /// `"exp.sha.5114f85"` for `1.0.0+exp.sha.5114f85`), or an empty string
+    /// when there is none.
+    ///
+    /// Cargo has no environment variable for it, so it's parsed from
+    /// `CARGO_PKG_VERSION`.
+    VersionBuild,
+
+    /// Equivalent to `PROFILE` in environment variables passed to `build.rs'.
+    ///
+    /// Should usually be `"debug"` or `"release"`.
+    Profile,
+
+    /// Equivalent to the `RUSTFLAGS` environment variable.
+    ///
+    /// Note that this isn't _all_ of the flags passed to `rustc`, but instead
+    /// it is only the custom extra flags.
+    RustFlags,
+
+    /// Equivalent to the `CARGO_PKG_NAME` environment variable.
+    Name,
+
+    /// Equivalent to the `CARGO_PKG_AUTHORS` environment variable.
+    Authors,
+
+    /// Equivalent to the `CARGO_PKG_DESCRIPTION` environment variable.
+    Description,
+
+    /// Equivalent to the `CARGO_PKG_HOMEPAGE` environment variable.
+    Homepage,
+
+    /// Equivalent to the `OPT_LEVEL` environment variable in `build.rs`.
+    OptLevel,
+
+    /// Debuginfo level of the build, from the `DEBUG` environment variable in
+    /// `build.rs`.
+    ///
+    /// Cargo passes either a level, `0`, `1`, or `2`, or a boolean, in which
+    /// case `true` counts as `2` (full debuginfo) and `false` as `0`. When
+    /// optional, `None` means `DEBUG` isn't set or isn't one of these.
+    DebugLevel,
+
+    /// Equivalent to the `CARGO_CFG_*` environment variables in `build.rs`.
+    Cfg,
+
+    /// Equivalent to the `CARGO_FEATURE_*` environment variables in `build.rs`.
+    Features,
+
+    /// Number of codegen units, from `-C codegen-units=N` in
+    /// `CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS`.
+    CodegenUnits,
+
+    /// Path to the root of the Cargo workspace, or the crate's own directory
+    /// when it isn't part of a workspace.
+    ///
+    /// Not included in [`BuildDetails::all`], since it embeds a local path.
+    WorkspaceRoot,
+
+    /// The working directory of `build.rs` when the details were generated.
+    ///
+    /// Not included in [`BuildDetails::all`], since it embeds a local path.
+    BuildCwd,
+
+    /// The linker explicitly configured with `-C linker=...` in the `rustc`
+    /// flags, or with the `CARGO_TARGET_<triple>_LINKER` environment variable.
+    ///
+    /// When optional, `None` means the default linker was used.
+    Linker,
+
+    /// Name and email of the author of the `HEAD` commit, as reported by
+    /// `git log -1 --format='%an <%ae>'`.
+    ///
+    /// Not included in [`BuildDetails::all`], since it embeds personal
+    /// information.
+    GitAuthor,
+
+    /// Abbreviated hash of the `HEAD` commit, as reported by
+    /// `git rev-parse --short HEAD`.
+    ///
+    /// When optional, `None` means the build isn't inside a repository.
+    GitCommitShort,
+
+    /// Full name of the ref checked out at `HEAD` (like `"refs/heads/main"`),
+    /// as reported by `git symbolic-ref -q HEAD`.
+    ///
+    /// When optional, `None` means `HEAD` is detached or the build isn't
+    /// inside a repository. Not included in [`BuildDetails::all`]:
+    /// [`BuildDetails::generate`] prints a `cargo:rerun-if-changed` directive
+    /// for `HEAD`, which stops Cargo from rerunning the build script whenever
+    /// any file in the package changes.
+    GitRef,
+
+    /// The upstream the checked out branch tracks (like `"origin/main"`), as
+    /// reported by `git rev-parse --abbrev-ref --symbolic-full-name @{u}`.
+    ///
+    /// When optional, `None` means the branch has no upstream, `HEAD` is
+    /// detached, or the build isn't inside a repository.
+    GitUpstream,
+
+    /// The subject, or first line of the message, of the `HEAD` commit, as
+    /// reported by `git log -1 --format=%s`.
+    ///
+    /// When optional, `None` means there are no commits or the build isn't
+    /// inside a repository.
+    GitCommitSubject,
+
+    /// The CPU targeted with `-C target-cpu=...` in the `rustc` flags (like
+    /// `native` or `x86-64-v2`).
+    ///
+    /// See [`BuildDetails::target_cpu_fallback`] for when no CPU is given.
+    TargetCpu,
+
+    /// Operating system of the machine running the build, followed by its
+    /// version when it can be determined (like `"linux Ubuntu 22.04.3 LTS"`).
+    ///
+    /// Not included in [`BuildDetails::all`], since it describes the build
+    /// infrastructure.
+    BuildHostOs,
+
+    /// Whether incremental compilation was enabled, from the
+    /// `CARGO_INCREMENTAL` environment variable or `-C incremental` in the
+    /// `rustc` flags.
+    Incremental,
+
+    /// The panic strategy selected with `-C panic=...` in the `rustc` flags
+    /// (`unwind` or `abort`).
+    ///
+    /// Cargo doesn't pass the profile's `panic` setting to build scripts, so
+    /// only the flags are consulted. See
+    /// [`BuildDetails::panic_strategy_fallback`] for when no strategy is given.
+    PanicStrategy,
+
+    /// The enabled Cargo features, sorted and joined with commas (like
+    /// `"DEFAULT,SERDE"`). Empty when no features are enabled.
+    ///
+    /// Spelled like [`BuildDetail::Features`], as Cargo provides them to build
+    /// scripts.
+    FeaturesString,
+
+    /// Number of entries in `git stash list`, to flag builds made with
+    /// stashed work pending.
+    ///
+    /// Not included in [`BuildDetails::all`]: [`BuildDetails::generate`]
+    /// prints a `cargo:rerun-if-changed` directive for the stash, which stops
+    /// Cargo from rerunning the build script whenever any file in the package
+    /// changes.
+    GitStashCount,
+
+    /// The rustup toolchain used for the build (like
+    /// `"stable-x86_64-unknown-linux-gnu"`), from the `RUSTUP_TOOLCHAIN`
+    /// environment variable.
+    ///
+    /// When optional, `None` means the build didn't go through rustup. Not
+    /// included in [`BuildDetails::all`]: [`BuildDetails::generate`] prints a
+    /// `cargo:rerun-if-env-changed` directive for the variable, which stops
+    /// Cargo from rerunning the build script whenever any file in the package
+    /// changes.
+    ToolchainName,
+
+    /// The `OUT_DIR` directory the build script generates into, for debugging
+    /// the location of generated assets.
+    ///
+    /// Not included in [`BuildDetails::all`], since it embeds a local path
+    /// that differs between machines, making the build non-reproducible.
+    OutDir,
+
+    /// Date and time of the build in the build machine's timezone, in RFC 3339
+    /// format (like `"2017-07-14T04:40:00+02:00"`).
+    ///
+    /// The timezone comes from the `TZ` environment variable, falling back to
+    /// the system's. When optional, `None` means the offset couldn't be
+    /// determined. Not included in [`BuildDetails::all`], since it isn't
+    /// reproducible unless both `TZ` and `SOURCE_DATE_EPOCH` are set.
+    BuildDateLocal,
+
+    /// Offset of the build machine's timezone from UTC at the time of the
+    /// build, in seconds east of UTC (like `7200` for `+02:00`), to turn
+    /// [`BuildDetail::Timestamp`] into local time.
+    ///
+    /// The timezone is determined like for [`BuildDetail::BuildDateLocal`].
+    /// When optional, `None` means the offset couldn't be determined. Not
+    /// included in [`BuildDetails::all`], since it isn't reproducible unless
+    /// `TZ` is set.
+    BuildTzOffset,
+
+    /// Whether any vendored dependency differs from the checksums recorded by
+    /// `cargo vendor`, to flag builds made with modified vendored code.
+    ///
+    /// The vendor directory is the `directory` of a `[source.*]` table in the
+    /// workspace's `.cargo/config.toml`, or `vendor` in the workspace root.
+    /// When optional, `None` means there is no vendor directory. Not included
+    /// in [`BuildDetails::all`], since every vendored file is hashed.
+    VendorDirty,
+
+    /// The sanitizers enabled with `-Z sanitizer=...` in the `rustc` flags
+    /// (like `address` or `thread`), sorted. Empty when none are enabled.
+    ///
+    /// Sanitizer builds shouldn't be shipped, which this allows checking at
+    /// runtime.
+    Sanitizers,
+
+    /// Absolute path of the root of the `git` checkout being built, as
+    /// reported by `git rev-parse --show-toplevel`.
+    ///
+    /// When optional, `None` means the build isn't inside a repository. Not
+    /// included in [`BuildDetails::all`], since it embeds a local path that
+    /// differs between machines, making the build non-reproducible.
+    GitWorktree,
+
+    /// Total number of lines in the `.rs` files under the crate's `src`
+    /// directory.
+    ///
+    /// Not included in [`BuildDetails::all`]: [`BuildDetails::generate`]
+    /// prints a `cargo:rerun-if-changed` directive for `src`, which stops
+    /// Cargo from rerunning the build script when other files in the package
+    /// change.
+    SourceLoc,
+
+    /// Whether the C runtime is linked statically, with the `crt-static`
+    /// target feature, which affects where the binary can run.
+    ///
+    /// Cargo provides the enabled target features to build scripts; otherwise
+    /// `-C target-feature=...` in the `rustc` flags is consulted, and `false`
+    /// means the feature isn't enabled there.
+    StaticCrt,
+
+    /// The `opt-level`, `lto`, `codegen-units`, and `debug` settings of the
+    /// profile being built, as a `ProfileOverrides` struct generated at the
+    /// top level, with a field for each that is `None` when the setting isn't
+    /// overridden.
+    ///
+    /// Like Cargo, the `CARGO_PROFILE_<NAME>_<KEY>` environment variables take
+    /// precedence over the workspace's `.cargo/config.toml`, which takes
+    /// precedence over the root `Cargo.toml`. This is best effort: only
+    /// `key = value` lines of a `[profile.dev]` or `[profile.release]` table
+    /// are read, so custom profiles, inheritance, dotted keys, and
+    /// configuration outside the workspace are missed. Values are kept as
+    /// written, like `"3"` or `"fat"`, except `codegen-units`, which is a
+    /// `u32`.
+    ///
+    /// When optional, `None` means the root manifest couldn't be read, or
+    /// `PROFILE` isn't set. Not included in [`BuildDetails::all`], since the
+    /// struct's name could clash with the crate's own items.
+    ProfileOverrides,
+
+    /// The target `rustc` builds for by default (like
+    /// `"x86_64-unknown-linux-gnu"`), which can differ from the machine
+    /// running the build in unusual setups.
+    ///
+    /// When optional, `None` means the compiler couldn't be asked. Not
+    /// included in [`BuildDetails::all`], since it runs the compiler again.
+    DefaultTarget,
+
+    /// The host `rustc` was built for (like `"x86_64-unknown-linux-gnu"`),
+    /// from the `host` line of `rustc -vV`. This is the compiler's own
+    /// record, unlike the `HOST` Cargo passes to build scripts.
+    ///
+    /// When optional, `None` means the compiler couldn't be asked, or didn't
+    /// print a `host` line. Not included in [`BuildDetails::all`], since it
+    /// runs the compiler again.
+    RustcHost,
+
+    /// The dependency resolver of the workspace (like `"2"`), which decides
+    /// how features of shared dependencies are unified.
+    ///
+    /// Read from the `resolver` key of the workspace's root `Cargo.toml`, or
+    /// implied by its edition like Cargo does. When optional, `None` means the
+    /// manifest couldn't be found. Not included in [`BuildDetails::all`],
+    /// since it's only useful when debugging feature unification.
+    ResolverVersion,
+
+    /// The `cargo:rerun-if-changed` and `cargo:rerun-if-env-changed`
+    /// directives printed by [`BuildDetails::generate`] (without the `cargo:`
+    /// prefix), sorted, so the binary can report what its build depended on.
+    ///
+    /// Not included in [`BuildDetails::all`], since the directives can embed
+    /// local paths, making the build non-reproducible.
+    RerunInputs,
+
+    /// The features declared in the `[features]` table of the package's
+    /// `Cargo.toml`, as `FEATURES_AVAILABLE`, and which of them are enabled, as
+    /// `FEATURES_ENABLED`, so the binary can report features it was built
+    /// without.
+    ///
+    /// Both are sorted, and spelled like in `Cargo.toml`. Features implied by
+    /// optional dependencies aren't listed. When optional, `None` means the
+    /// manifest couldn't be read.
+    FeatureFlags,
+
+    /// The kind of link-time optimization selected with `-C lto` in the
+    /// `rustc` flags: `"off"`, `"thin"`, `"fat"`, or `"thin-local"`.
+    ///
+    /// Without the flag, `rustc` does `"thin-local"` LTO across the crate's
+    /// codegen units when optimizing (going by the `OPT_LEVEL` Cargo gives
+    /// build scripts) with more than one codegen unit, and `"off"` otherwise.
+    /// Cargo doesn't pass the profile's `lto` setting to build scripts, so
+    /// only the flags are consulted. When optional, `None` means the flag's
+    /// value wasn't recognized.
+    LtoKind,
+
+    /// The locale active during the build, from the `LC_ALL` environment
+    /// variable, or `LANG` when `LC_ALL` isn't set.
+    ///
+    /// When optional, `None` means neither is set. Not included in
+    /// [`BuildDetails::all`], since it depends on the machine running the
+    /// build, and [`BuildDetails::generate`] prints
+    /// `cargo:rerun-if-env-changed` directives for the variables, which stop
+    /// Cargo from rerunning the build script whenever any file in the package
+    /// changes.
+    BuildLocale,
+
+    /// The shell of the user running the build, from the `SHELL` environment
+    /// variable, like `"/bin/bash"`.
+    ///
+    /// When optional, `None` means `SHELL` isn't set, which is usual on
+    /// Windows. Not included in [`BuildDetails::all`], since it depends on the
+    /// machine running the build, and [`BuildDetails::generate`] prints a
+    /// `cargo:rerun-if-env-changed` directive for `SHELL`.
+    BuildShell,
+
+    /// Whether Cargo was told not to access the network, with the
+    /// `CARGO_NET_OFFLINE` environment variable, so dependencies could only
+    /// come from the local cache.
+    ///
+    /// Cargo doesn't tell build scripts about `--offline`, `--frozen`, or the
+    /// `net.offline` setting, so those builds are only detected when the
+    /// variable is set, and `false` means it wasn't. Not included in
+    /// [`BuildDetails::all`], since [`BuildDetails::generate`] prints a
+    /// `cargo:rerun-if-env-changed` directive for the variable.
+    CargoOffline,
+
+    /// The package version, marked with the abbreviated `HEAD` commit unless
+    /// the build is of a clean, tagged commit (like `"1.2.0"` for a release,
+    /// or `"1.2.0-3f2a1bc-dirty"` for uncommitted work).
+    ///
+    /// The marked version follows [`BuildDetails::smart_version_template`].
+    /// When the build isn't inside a repository, or `git` isn't installed, the
+    /// version is left unmarked.
+    SmartVersion,
+
+    /// Number of entries in the `CARGO_PKG_AUTHORS` environment variable,
+    /// which separates them with colons. Zero when there are no authors.
+    AuthorCount,
+
+    /// The `categories` of the package, from `Cargo.toml`. Empty when none
+    /// are declared.
+    ///
+    /// When optional, `None` means the manifest couldn't be read.
+    Categories,
+
+    /// The `keywords` of the package, from `Cargo.toml`. Empty when none are
+    /// declared.
+    ///
+    /// When optional, `None` means the manifest couldn't be read.
+    Keywords,
+
+    /// The optional dependencies of the package that were enabled, as
+    /// `OPTIONAL_DEPS_ENABLED`, to see why a dependency was or wasn't pulled
+    /// into the build.
+    ///
+    /// Optional dependencies are read from `Cargo.toml`, and count as enabled
+    /// when Cargo sets the `CARGO_FEATURE_*` variable of their implicit
+    /// feature. Dependencies only referred to as `dep:name` in `[features]`
+    /// have no implicit feature, so they're never listed. Sorted, and spelled
+    /// like in `Cargo.toml`. When optional, `None` means the manifest
+    /// couldn't be read.
+    OptionalDeps,
+
+    /// SHA-256 of the workspace's `Cargo.lock`, as lowercase hexadecimal.
+    /// Builds with the same hash used exactly the same dependencies.
+    ///
+    /// When optional, `None` means there's no lockfile. Not included in
+    /// [`BuildDetails::all`]: [`BuildDetails::generate`] prints a
+    /// `cargo:rerun-if-changed` directive for `Cargo.lock`, which stops Cargo
+    /// from rerunning the build script whenever any file in the package
+    /// changes.
+    LockfileHash,
+
+    /// Number of packages in the workspace, from the `members` and `exclude`
+    /// of the root `Cargo.toml`'s `[workspace]`, including the root package.
+    /// One when the crate isn't in a workspace.
+    ///
+    /// Only `*` wildcards are expanded in members. When optional, `None` means
+    /// the manifest couldn't be read.
+    WorkspaceMemberCount,
+
+    /// A random hex string generated anew for every build, usable as a
+    /// cache-busting token or to tell otherwise identical builds apart.
+    ///
+    /// Not included in [`BuildDetails::all`], since it makes every build
+    /// different.
+    BuildNonce,
+
+    #[doc(hidden)]
+    #[cfg_attr(feature = "config", serde(skip))]
+    __Nonexhaustive,
+}
+
+impl BuildDetail {
+    /// Bit of this detail in `BUILD_FLAGS`, for boolean details. See
+    /// [`BuildDetails::build_flags`]. Bits must never be reassigned.
+    fn flag_bit(self) -> Option<u32> {
+        match self {
+            BuildDetail::Incremental => Some(0),
+            BuildDetail::StaticCrt => Some(1),
+            BuildDetail::VendorDirty => Some(2),
+            BuildDetail::CargoOffline => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Name of the module holding this detail in namespaced output.
+    fn category(self) -> &'static str {
+        use self::BuildDetail::*;
+
+        match self {
+            Version | VersionBuild | Name | Authors | Description | Homepage | Features
+            | FeaturesString | FeatureFlags | AuthorCount | Categories | Keywords
+            | OptionalDeps => "pkg",
+
+            Profile | RustFlags | OptLevel | DebugLevel | Cfg | CodegenUnits | Linker
+            | TargetCpu | Incremental | PanicStrategy | ToolchainName | Sanitizers | StaticCrt
+            | DefaultTarget | RustcHost | LtoKind | ProfileOverrides => "toolchain",
+
+            GitAuthor | GitCommitShort | GitRef | GitUpstream | GitCommitSubject
+            | GitStashCount | GitWorktree | SmartVersion => "git",
+
+            Timestamp | WorkspaceRoot | BuildCwd | BuildHostOs | OutDir | BuildDateLocal
+            | BuildTzOffset | VendorDirty | BuildNonce | ResolverVersion | RerunInputs
+            | BuildLocale | BuildShell | CargoOffline | LockfileHash | WorkspaceMemberCount
+            | SourceLoc => "build",
+
+            __Nonexhaustive => unreachable!(),
+        }
+    }
+
+    /// The `cargo:` directives (without the prefix) that keep this detail up
+    /// to date.
+    fn directives(self) -> Vec<String> {
+        match self {
+            BuildDetail::GitStashCount => git::stash_paths()
+                .into_iter()
+                .map(|path| format!("rerun-if-changed={}", path.display()))
+                .collect(),
+            BuildDetail::GitRef => git::git(&["rev-parse", "--git-path", "HEAD"])
+                .map(|path| format!("rerun-if-changed={}", path))
+                .into_iter()
+                .collect(),
+            BuildDetail::ToolchainName => vec!["rerun-if-env-changed=RUSTUP_TOOLCHAIN".to_owned()],
+            BuildDetail::BuildLocale => vec![
+                "rerun-if-env-changed=LANG".to_owned(),
+                "rerun-if-env-changed=LC_ALL".to_owned(),
+            ],
+            BuildDetail::BuildShell => vec!["rerun-if-env-changed=SHELL".to_owned()],
+            BuildDetail::Timestamp | BuildDetail::BuildDateLocal | BuildDetail::BuildTzOffset
+                if env::var_os("SOURCE_DATE_EPOCH").is_some() =>
+            {
+                vec!["rerun-if-env-changed=SOURCE_DATE_EPOCH".to_owned()]
+            }
+            BuildDetail::CargoOffline => vec!["rerun-if-env-changed=CARGO_NET_OFFLINE".to_owned()],
+            BuildDetail::LockfileHash => manifest::lockfile()
+                .map(|path| format!("rerun-if-changed={}", path.display()))
+                .into_iter()
+                .collect(),
+            BuildDetail::SourceLoc => source::dir()
+                .map(|dir| format!("rerun-if-changed={}", dir.display()))
+                .into_iter()
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The name and type of the constant generated for this detail, when
+    /// [`BuildDetails::docs_rs_safe`] replaces it with a placeholder.
+    ///
+    /// Details from the package's metadata, and those of types without a
+    /// placeholder, are `None`.
+    fn placeholder(self, options: &BuildDetails) -> Option<(&'static str, &'static str)> {
+        use self::BuildDetail::*;
+
+        let placeholder = match self {
+            Timestamp if options.timestamp_signed => ("TIMESTAMP", "i64"),
+            Timestamp => ("TIMESTAMP", "u64"),
+            RustFlags => ("RUST_FLAGS", STR_TYPE),
+            Profile => ("PROFILE", STR_TYPE),
+            OptLevel => ("OPT_LEVEL", STR_TYPE),
+            DebugLevel => ("DEBUG_LEVEL", "u8"),
+            ToolchainName => ("TOOLCHAIN", STR_TYPE),
+            OutDir => ("OUT_DIR", STR_TYPE),
+            BuildDateLocal => ("BUILD_DATE_LOCAL", STR_TYPE),
+            BuildTzOffset => ("BUILD_TZ_OFFSET", "i32"),
+            VendorDirty => ("VENDOR_DIRTY", "bool"),
+            Sanitizers => ("SANITIZERS", LIST_TYPE),
+            StaticCrt => ("STATIC_CRT", "bool"),
+            DefaultTarget => ("DEFAULT_TARGET", STR_TYPE),
+            RustcHost => ("RUSTC_HOST", STR_TYPE),
+            SourceLoc => ("SOURCE_LOC", "u32"),
+            BuildNonce => ("BUILD_NONCE", STR_TYPE),
+            CodegenUnits => ("CODEGEN_UNITS", "u32"),
+            LtoKind => ("LTO_KIND", STR_TYPE),
+            BuildLocale => ("BUILD_LOCALE", STR_TYPE),
+            BuildShell => ("BUILD_SHELL", STR_TYPE),
+            CargoOffline => ("CARGO_OFFLINE", "bool"),
+            WorkspaceRoot => ("WORKSPACE_ROOT", STR_TYPE),
+            ResolverVersion => ("RESOLVER_VERSION", STR_TYPE),
+            LockfileHash => ("LOCKFILE_HASH", STR_TYPE),
+            WorkspaceMemberCount => ("WORKSPACE_MEMBER_COUNT", "u32"),
+            RerunInputs => ("RERUN_INPUTS", LIST_TYPE),
+            BuildCwd => ("BUILD_CWD", STR_TYPE),
+            Linker => ("LINKER", STR_TYPE),
+            Incremental => ("INCREMENTAL", "bool"),
+            TargetCpu => ("TARGET_CPU", STR_TYPE),
+            PanicStrategy => ("PANIC_STRATEGY", STR_TYPE),
+            BuildHostOs => ("BUILD_HOST_OS", STR_TYPE),
+            GitAuthor => ("GIT_AUTHOR", STR_TYPE),
+            GitStashCount => ("GIT_STASH_COUNT", "u32"),
+            GitWorktree => ("GIT_WORKTREE", STR_TYPE),
+            GitCommitShort => ("GIT_COMMIT_SHORT", STR_TYPE),
+            GitRef => ("GIT_REF", STR_TYPE),
+            GitCommitSubject => ("GIT_COMMIT_SUBJECT", STR_TYPE),
+            GitUpstream => ("GIT_UPSTREAM", STR_TYPE),
+            SmartVersion => ("SMART_VERSION", STR_TYPE),
+
+            Version | VersionBuild | Name | Authors | Description | Homepage | Features
+            | FeaturesString | FeatureFlags | AuthorCount | Categories | Keywords
+            | OptionalDeps | Cfg | ProfileOverrides => return None,
+
+            __Nonexhaustive => unreachable!(),
+        };
+
+        Some(placeholder)
+    }
+
+    /// Constants generated alongside the one from `into_render`, for details
+    /// made of several values.
+    fn companions(self) -> Vec<Box<dyn Constant>> {
+        match self {
+            BuildDetail::FeatureFlags => vec![Box::from(FeatureFlags::enabled())],
+            _ => vec![],
+        }
+    }
+
+    fn into_render(self, options: &BuildDetails) -> Box<dyn Constant> {
+        use self::BuildDetail::*;
+
+        match self {
+            Timestamp if options.timestamp_signed => Box::from(self::Timestamp::signed()),
+            Timestamp => Box::from(self::Timestamp::new()),
+
+            Version => Box::from(Env::new("VERSION", "CARGO_PKG_VERSION")),
+            VersionBuild => Box::from(BuildEnv::with_value(
+                "VERSION_BUILD",
+                env::var("CARGO_PKG_VERSION")
+                    .ok()
+                    .map(|x| version::build(&x).to_owned()),
+            )),
+            Name => Box::from(Env::new("NAME", "CARGO_PKG_NAME")),
+            Authors => Box::from(Env::new("AUTHORS", "CARGO_PKG_AUTHORS")),
+            Description => Box::from(Env::new("DESCRIPTION", "CARGO_PKG_DESCRIPTION")),
+            Homepage => Box::from(Env::new("HOMEPAGE", "CARGO_PKG_HOMEPAGE")),
+            RustFlags => Box::from(Env::new("RUST_FLAGS", "RUSTFLAGS")),
+
+            Profile => Box::from(BuildEnv::new("PROFILE", "PROFILE")),
+            OptLevel => Box::from(BuildEnv::new("OPT_LEVEL", "OPT_LEVEL")),
+            DebugLevel => Box::from(self::DebugLevel::new()),
+            ToolchainName => Box::from(BuildEnv::new("TOOLCHAIN", "RUSTUP_TOOLCHAIN")),
+            OutDir => Box::from(BuildEnv::new("OUT_DIR", "OUT_DIR")),
+            BuildDateLocal => Box::from(self::BuildDateLocal::new()),
+            BuildTzOffset => Box::from(self::BuildTzOffset::new()),
+            VendorDirty => Box::from(self::VendorDirty::new()),
+            Sanitizers => Box::from(self::Sanitizers::new()),
+            StaticCrt => Box::from(self::StaticCrt::new()),
+            ProfileOverrides => Box::from(self::ProfileOverrides::new()),
+            DefaultTarget => Box::from(BuildEnv::with_value(
+                "DEFAULT_TARGET",
+                rustc::default_target(),
+            )),
+            RustcHost => Box::from(BuildEnv::with_value(
+                "RUSTC_HOST",
+                rustc::verbose_version("host"),
+            )),
+            SourceLoc => Box::from(self::SourceLoc::new()),
+            BuildNonce => Box::from(BuildEnv::with_value("BUILD_NONCE", Some(nonce::generate()))),
+
+            Cfg => Box::from(BuildEnvMap::new(
+                "CFG",
+                "CARGO_CFG_",
+                &options.phf_path,
+                options.ordered_cfg,
+            )),
+            Features => Box::from(BuildEnvList::new("FEATURES", "CARGO_FEATURE_")),
+            FeaturesString => Box::from(self::FeaturesString::new()),
+            AuthorCount => Box::from(self::AuthorCount::new()),
+            Categories => Box::from(PackageList::new("CATEGORIES", "categories")),
+            Keywords => Box::from(PackageList::new("KEYWORDS", "keywords")),
+            OptionalDeps => Box::from(self::OptionalDeps::new()),
+            FeatureFlags => Box::from(self::FeatureFlags::available()),
+
+            CodegenUnits => Box::from(self::CodegenUnits::new()),
+            LtoKind => Box::from(self::LtoKind::new()),
+            BuildLocale => Box::from(self::BuildLocale::new()),
+            BuildShell => Box::from(BuildEnv::new("BUILD_SHELL", "SHELL")),
+            CargoOffline => Box::from(self::CargoOffline::new()),
+            WorkspaceRoot => Box::from(self::WorkspaceRoot::new()),
+            ResolverVersion => Box::from(self::ResolverVersion::new()),
+            LockfileHash => Box::from(self::LockfileHash::new()),
+            WorkspaceMemberCount => Box::from(self::WorkspaceMemberCount::new()),
+            RerunInputs => Box::from(Detail {
+                name: "RERUN_INPUTS".into(),
+                value_type: LIST_TYPE.into(),
+                value: BuildEnvList(options.directives()),
+            }),
+            BuildCwd => Box::from(self::BuildCwd::new()),
+            Linker => Box::from(self::Linker::new()),
+            Incremental => Box::from(self::Incremental::new()),
+            TargetCpu => Box::from(self::TargetCpu::new(&options.target_cpu_fallback)),
+            PanicStrategy => Box::from(self::PanicStrategy::new(&options.panic_strategy_fallback)),
+            BuildHostOs => Box::from(BuildEnv::with_value("BUILD_HOST_OS", Some(host::os()))),
+            GitAuthor => Box::from(Git::new("GIT_AUTHOR", &["log", "-1", "--format=%an <%ae>"])),
+            GitStashCount => Box::from(self::GitStashCount::new()),
+            GitWorktree => Box::from(Git::new("GIT_WORKTREE", &["rev-parse", "--show-toplevel"])),
+            GitCommitShort => Box::from(Git::new(
+                "GIT_COMMIT_SHORT",
+                &["rev-parse", "--short", "HEAD"],
+            )),
+            GitRef => Box::from(Git::new("GIT_REF", &["symbolic-ref", "-q", "HEAD"])),
+            GitCommitSubject => Box::from(Git::new(
+                "GIT_COMMIT_SUBJECT",
+                &["log", "-1", "--format=%s"],
+            )),
+            GitUpstream => Box::from(Git::new(
+                "GIT_UPSTREAM",
+                &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+            )),
+            SmartVersion => Box::from(self::SmartVersion::new(&options.smart_version_template)),
+
+            __Nonexhaustive => unreachable!(),
+        }
+    }
+}
+
+struct Detail<T>
+where
+    T: Render,
+{
+    name: Cow<'static, str>,
+    value_type: Cow<'static, str>,
+    value: T,
+}
+
+impl<T> Render for Detail<T>
+where
+    T: Render,
+{
+    fn render_option(&self) -> Result<String> {
+        let value = self.value.render_option()?;
+
+        Ok(format!(
+            "pub const {}: Option<{}> = {};",
+            self.name, self.value_type, value
+        ))
+    }
+
+    fn render(&self) -> Result<String> {
+        let value = match self.value.render() {
+            Ok(x) => x,
+            Err(Error::Missing) => {
+                return Err(Error::MissingDetail(self.name.to_string()));
+            }
+            e => return e,
+        };
+
+        Ok(format!(
+            "pub const {}: {} = {};",
+            self.name, self.value_type, value
+        ))
+    }
+
+    fn render_placeholder(&self, placeholder: &str) -> Result<String> {
+        let value = self.value.render_placeholder(placeholder)?;
+
+        Ok(format!(
+            "pub const {}: Option<{}> = {};",
+            self.name, self.value_type, value
+        ))
+    }
+
+    fn render_wrapped(&self, wrapper: &OptionalWrapper) -> Result<String> {
+        let value = self.value.render_wrapped(wrapper)?;
+
+        Ok(format!(
+            "pub const {}: {} = {};",
+            self.name,
+            wrapper.ty(&self.value_type),
+            value
+        ))
+    }
+
+    fn resolve(&self) -> Option<String> {
+        self.value.resolve()
+    }
+
+    fn missing(&self) -> bool {
+        self.value.missing()
+    }
+
+    fn plain(&self) -> bool {
+        self.value.plain()
+    }
+
+    fn declaration(&self) -> Option<String> {
+        self.value.declaration()
+    }
+}
+
+const STR_TYPE: &str = "&'static str";
+const LIST_TYPE: &str = "&'static [&'static str]";
+const BYTES_TYPE: &str = "&'static [u8]";
+const COW_TYPE: &str = "::std::borrow::Cow<'static, str>";
+
+trait Render {
+    fn render_option(&self) -> Result<String>;
+    fn render(&self) -> Result<String>;
+
+    /// The value as text, determined while generating, or `None` if it is
+    /// missing or can't be represented as text.
+    fn resolve(&self) -> Option<String>;
+
+    /// Like [`Render::render_option`], but substitutes `placeholder` when the
+    /// value is missing.
+    fn render_placeholder(&self, placeholder: &str) -> Result<String> {
+        match self.render() {
+            Ok(x) => Ok(format!("Some({})", x)),
+            Err(Error::Missing) => Ok(format!("Some({:?})", placeholder)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Like [`Render::render_option`], but with a custom optional type.
+    fn render_wrapped(&self, wrapper: &OptionalWrapper) -> Result<String> {
+        match self.render() {
+            Ok(x) => Ok(wrapper.some(&x)),
+            Err(Error::Missing) => Ok(wrapper.none()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether the value couldn't be determined.
+    fn missing(&self) -> bool {
+        matches!(self.render(), Err(Error::Missing))
+    }
+
+    /// Whether the value is `Copy`, comparable, and serializable by `serde`,
+    /// so it can be a field of the generated `BuildInfo` struct.
+    fn plain(&self) -> bool {
+        true
+    }
+
+    /// The declaration of the value's type, like a struct, when it's generated
+    /// too. It's written once at the top level, before the constants, and
+    /// imported into modules using it, so it must declare the constant's own
+    /// value type.
+    fn declaration(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A [`Render`] that produces a complete, named constant.
+trait Constant: Render {
+    fn name(&self) -> &str;
+    fn value_type(&self) -> &str;
+    fn rename(&mut self, name: String);
+}
+
+impl<T> Constant for Detail<T>
+where
+    T: Render,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn value_type(&self) -> &str {
+        &self.value_type
+    }
+
+    fn rename(&mut self, name: String) {
+        self.name = name.into();
+    }
+}
+
+/// A value that can be written as a Rust literal of its own type.
+trait Literal: fmt::Display {
+    fn literal(&self) -> String;
+}
+
+impl Literal for bool {
+    fn literal(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Integers are written with a type suffix (like `1234u64`), so they stay
+/// unambiguous wherever the generated value ends up.
+macro_rules! suffixed_literal {
+    ($($ty:ident),*) => {
+        $(
+            impl Literal for $ty {
+                fn literal(&self) -> String {
+                    format!("{}{}", self, stringify!($ty))
+                }
+            }
+        )*
+    };
+}
+
+suffixed_literal!(u8, u32, u64, i32, i64);
+
+impl<T> Render for Option<T>
+where
+    T: Literal,
+{
+    fn render_option(&self) -> Result<String> {
+        match self {
+            Some(x) => Ok(format!("Some({})", x.literal())),
+            None => Ok("None".to_owned()),
+        }
+    }
+
+    fn render(&self) -> Result<String> {
+        match self {
+            Some(x) => Ok(x.literal()),
+            None => Err(Error::Missing),
+        }
+    }
+
+    fn resolve(&self) -> Option<String> {
+        self.as_ref().map(T::to_string)
+    }
+}
+
+struct Timestamp;
+
+impl Timestamp {
+    pub fn new() -> Detail<Option<u64>> {
+        // TODO: Touch build.rs to trigger a rebuild every time
+
+        Detail {
+            name: "TIMESTAMP".into(),
+            value_type: "u64".into(),
+            value: checked_build_time(),
+        }
+    }
+
+    pub fn signed() -> Detail<Option<i64>> {
+        let unsigned = Self::new();
+
+        Detail {
+            name: unsigned.name,
+            value_type: "i64".into(),
+            value: unsigned.value.and_then(|x| i64::try_from(x).ok()),
+        }
+    }
+}
+
+/// The `SOURCE_DATE_EPOCH` environment variable, if set, failing with
+/// [`Error::InvalidSourceDateEpoch`] when it isn't a number of seconds.
+fn source_date_epoch() -> Result<Option<u64>> {
+    match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => match epoch.trim().parse() {
+            Ok(secs) => Ok(Some(secs)),
+            Err(_) => Err(Error::InvalidSourceDateEpoch(epoch)),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+/// Seconds since the Unix epoch at which the build happened, honouring
+/// `SOURCE_DATE_EPOCH`, or `None` when the clock is before the epoch.
+fn build_time() -> Result<Option<u64>> {
+    if let Some(secs) = source_date_epoch()? {
+        return Ok(Some(secs));
+    }
+
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .as_ref()
+        .map(Duration::as_secs)
+        .ok())
+}
+
+/// Like [`build_time`], for details, whose `SOURCE_DATE_EPOCH` was already
+/// checked by [`BuildDetails::entries`].
+fn checked_build_time() -> Option<u64> {
+    build_time().unwrap_or(None)
+}
+
+struct BuildDateLocal;
+
+impl BuildDateLocal {
+    pub fn new() -> Detail<BuildEnv> {
+        let date = checked_build_time()
+            .and_then(|x| i64::try_from(x).ok())
+            .and_then(|secs| {
+                date::local_offset(secs).map(|offset| date::format_rfc3339(secs, offset))
+            });
+
+        BuildEnv::with_value("BUILD_DATE_LOCAL", date)
+    }
+}
+
+struct BuildTzOffset;
+
+impl BuildTzOffset {
+    pub fn new() -> Detail<Option<i32>> {
+        let offset = checked_build_time()
+            .and_then(|x| i64::try_from(x).ok())
+            .and_then(date::local_offset);
+
+        Detail {
+            name: "BUILD_TZ_OFFSET".into(),
+            value_type: "i32".into(),
+            value: offset,
+        }
+    }
+}
+
+struct CargoOffline;
+
+impl CargoOffline {
+    pub fn new() -> Detail<Option<bool>> {
+        let offline = env::var("CARGO_NET_OFFLINE")
+            .ok()
+            .and_then(|x| parse_bool(&x))
+            .unwrap_or(false);
+
+        Detail {
+            name: "CARGO_OFFLINE".into(),
+            value_type: "bool".into(),
+            value: Some(offline),
+        }
+    }
+}
+
+struct DebugLevel;
+
+impl DebugLevel {
+    pub fn new() -> Detail<Option<u8>> {
+        let level = env::var("DEBUG").ok().and_then(|x| match x.trim() {
+            "false" => Some(0),
+            "true" => Some(2),
+            level => level.parse().ok().filter(|x| *x <= 2),
+        });
+
+        Detail {
+            name: "DEBUG_LEVEL".into(),
+            value_type: "u8".into(),
+            value: level,
+        }
+    }
+}
+
+struct CodegenUnits;
+
+impl CodegenUnits {
+    pub fn new() -> Detail<Option<u32>> {
+        let units = RustFlags::from_env()
+            .codegen("codegen-units")
+            .and_then(|x| x.parse().ok());
+
+        Detail {
+            name: "CODEGEN_UNITS".into(),
+            value_type: "u32".into(),
+            value: units,
+        }
+    }
+}
+
+struct BuildLocale;
+
+impl BuildLocale {
+    pub fn new() -> Detail<BuildEnv> {
+        let locale = ["LC_ALL", "LANG"]
+            .iter()
+            .filter_map(|x| env::var(x).ok())
+            .find(|x| !x.is_empty());
+
+        BuildEnv::with_value("BUILD_LOCALE", locale)
+    }
+}
+
+struct LtoKind;
+
+impl LtoKind {
+    pub fn new() -> Detail<BuildEnv> {
+        let flags = RustFlags::from_env();
+
+        let kind = match flags.codegen("lto") {
+            Some("") | Some("y") | Some("yes") | Some("on") | Some("true") | Some("fat") => {
+                Some("fat")
+            }
+            Some("n") | Some("no") | Some("off") | Some("false") => Some("off"),
+            Some("thin") => Some("thin"),
+            Some(_) => None,
+            None => {
+                let optimized = env::var("OPT_LEVEL").is_ok_and(|x| x != "0");
+                let single_unit = flags.codegen("codegen-units") == Some("1");
+
+                if optimized && !single_unit {
+                    Some("thin-local")
+                } else {
+                    Some("off")
+                }
+            }
+        };
+
+        BuildEnv::with_value("LTO_KIND", kind.map(str::to_owned))
+    }
+}
+
+struct WorkspaceRoot;
+
+impl WorkspaceRoot {
+    pub fn new() -> Detail<BuildEnv> {
+        let root = env::var_os("CARGO_MANIFEST_DIR")
+            .map(|dir| manifest::workspace_root(Path::new(&dir)))
+            .and_then(|root| root.to_str().map(str::to_owned));
+
+        BuildEnv::with_value("WORKSPACE_ROOT", root)
+    }
+}
+
+struct LockfileHash;
+
+impl LockfileHash {
+    pub fn new() -> Detail<BuildEnv> {
+        let hash = manifest::lockfile()
+            .and_then(|path| manifest::read(&path))
+            .map(|text| {
+                let mut hasher = sha256::Sha256::new();
+                hasher.update(text.as_bytes());
+                hasher.hex()
+            });
+
+        BuildEnv::with_value("LOCKFILE_HASH", hash)
+    }
+}
+
+struct ProfileOverrides {
+    opt_level: Option<String>,
+    lto: Option<String>,
+    codegen_units: Option<u32>,
+    debug: Option<String>,
+}
+
+impl ProfileOverrides {
+    pub fn new() -> Detail<Option<Self>> {
+        Detail {
+            name: "PROFILE_OVERRIDES".into(),
+            value_type: "ProfileOverrides".into(),
+            value: Self::read(),
+        }
+    }
+
+    fn read() -> Option<Self> {
+        let dir = env::var_os("CARGO_MANIFEST_DIR")?;
+        let root = manifest::workspace_root(Path::new(&dir));
+        let manifest_path = root.join("Cargo.toml");
+
+        manifest::read(&manifest_path)?;
+
+        // `PROFILE` is only ever `debug` or `release`.
+        let profile = match env::var("PROFILE").ok()?.as_str() {
+            "debug" => "dev",
+            "release" => "release",
+            _ => return None,
+        };
+
+        let table = format!("profile.{}", profile);
+        let sources = [
+            root.join(".cargo").join("config.toml"),
+            root.join(".cargo").join("config"),
+            manifest_path,
+        ];
+
+        let setting = |key: &str| {
+            let var = format!(
+                "CARGO_PROFILE_{}_{}",
+                profile.to_uppercase(),
+                key.to_uppercase().replace('-', "_")
+            );
+
+            env::var(var)
+                .ok()
+                .or_else(|| sources.iter().find_map(|x| manifest::value(x, &table, key)))
+        };
+
+        Some(ProfileOverrides {
+            opt_level: setting("opt-level"),
+            lto: setting("lto"),
+            codegen_units: setting("codegen-units").and_then(|x| x.parse().ok()),
+            debug: setting("debug"),
+        })
+    }
+}
+
+impl Render for Option<ProfileOverrides> {
+    fn render_option(&self) -> Result<String> {
+        match self {
+            Some(_) => Ok(format!("Some({})", self.render()?)),
+            None => Ok("None".to_owned()),
+        }
+    }
+
+    fn render(&self) -> Result<String> {
+        let overrides = self.as_ref().ok_or(Error::Missing)?;
+
+        Ok(format!(
+            "ProfileOverrides {{ opt_level: {:?}, lto: {:?}, codegen_units: {}, debug: {:?} }}",
+            overrides.opt_level,
+            overrides.lto,
+            overrides.codegen_units.render_option()?,
+            overrides.debug,
+        ))
+    }
+
+    fn resolve(&self) -> Option<String> {
+        None
+    }
+
+    fn plain(&self) -> bool {
+        false
+    }
+
+    fn declaration(&self) -> Option<String> {
+        Some(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileOverrides {
+    pub opt_level: Option<&'static str>,
+    pub lto: Option<&'static str>,
+    pub codegen_units: Option<u32>,
+    pub debug: Option<&'static str>,
+}"
+            .to_owned(),
+        )
+    }
+}
+
+struct WorkspaceMemberCount;
+
+impl WorkspaceMemberCount {
+    pub fn new() -> Detail<Option<u32>> {
+        let count = env::var_os("CARGO_MANIFEST_DIR")
+            .map(|dir| manifest::workspace_root(Path::new(&dir)))
+            .and_then(|root| manifest::member_count(&root));
+
+        Detail {
+            name: "WORKSPACE_MEMBER_COUNT".into(),
+            value_type: "u32".into(),
+            value: count,
+        }
+    }
+}
+
+struct ResolverVersion;
+
+impl ResolverVersion {
+    pub fn new() -> Detail<BuildEnv> {
+        let resolver = env::var_os("CARGO_MANIFEST_DIR")
+            .map(|dir| manifest::workspace_root(Path::new(&dir)))
+            .and_then(|root| manifest::resolver(&root));
+
+        BuildEnv::with_value("RESOLVER_VERSION", resolver)
+    }
+}
+
+struct VendorDirty;
+
+impl VendorDirty {
+    pub fn new() -> Detail<Option<bool>> {
+        let dirty = env::var_os("CARGO_MANIFEST_DIR")
+            .map(|dir| manifest::workspace_root(Path::new(&dir)))
+            .and_then(|root| vendor::dirty(&vendor::dir(&root)));
+
+        Detail {
+            name: "VENDOR_DIRTY".into(),
+            value_type: "bool".into(),
+            value: dirty,
+        }
+    }
+}
+
+struct SourceLoc;
+
+impl SourceLoc {
+    pub fn new() -> Detail<Option<u32>> {
+        let loc = source::dir()
+            .and_then(|dir| source::lines_of_code(&dir).ok())
+            .and_then(|x| u32::try_from(x).ok());
+
+        Detail {
+            name: "SOURCE_LOC".into(),
+            value_type: "u32".into(),
+            value: loc,
+        }
+    }
+}
+
+struct BuildCwd;
+
+impl BuildCwd {
+    pub fn new() -> Detail<BuildEnv> {
+        let cwd = env::current_dir()
+            .ok()
+            .and_then(|dir| dir.to_str().map(str::to_owned));
+
+        BuildEnv::with_value("BUILD_CWD", cwd)
+    }
+}
+
+struct Linker;
+
+impl Linker {
+    pub fn new() -> Detail<BuildEnv> {
+        let linker = RustFlags::from_env()
+            .codegen("linker")
+            .map(str::to_owned)
+            .or_else(|| {
+                let target = env::var("TARGET").ok()?;
+                let var = format!(
+                    "CARGO_TARGET_{}_LINKER",
+                    target.to_uppercase().replace(['-', '.'], "_")
+                );
+                env::var(var).ok()
+            });
+
+        BuildEnv::with_value("LINKER", linker)
+    }
+}
+
+struct Incremental;
+
+impl Incremental {
+    pub fn new() -> Detail<Option<bool>> {
+        let incremental = match env::var("CARGO_INCREMENTAL") {
+            Ok(x) => parse_bool(&x),
+            Err(_) => RustFlags::from_env().codegen("incremental").map(|_| true),
+        };
+
+        Detail {
+            name: "INCREMENTAL".into(),
+            value_type: "bool".into(),
+            value: incremental,
+        }
+    }
+}
+
+struct TargetCpu;
+
+impl TargetCpu {
+    pub fn new(fallback: &Option<String>) -> Detail<BuildEnv> {
+        let cpu = RustFlags::from_env()
+            .codegen("target-cpu")
+            .map(str::to_owned)
+            .or_else(|| fallback.clone());
+
+        BuildEnv::with_value("TARGET_CPU", cpu)
+    }
+}
+
+struct Sanitizers;
+
+impl Sanitizers {
+    pub fn new() -> Detail<BuildEnvList> {
+        let flags = RustFlags::from_env();
+
+        let mut sanitizers: Vec<_> = flags
+            .unstable("sanitizer")
+            .into_iter()
+            .flat_map(|x| x.split(','))
+            .filter(|x| !x.is_empty())
+            .map(str::to_owned)
+            .collect();
+
+        sanitizers.sort();
+        sanitizers.dedup();
+
+        Detail {
+            name: "SANITIZERS".into(),
+            value_type: LIST_TYPE.into(),
+            value: BuildEnvList(sanitizers),
+        }
+    }
+}
+
+struct StaticCrt;
+
+impl StaticCrt {
+    pub fn new() -> Detail<Option<bool>> {
+        let enabled = match env::var("CARGO_CFG_TARGET_FEATURE") {
+            Ok(features) => features.split(',').any(|x| x == "crt-static"),
+            Err(_) => RustFlags::from_env()
+                .codegen_all("target-feature")
+                .into_iter()
+                .flat_map(|x| x.split(','))
+                .fold(false, |enabled, feature| match feature {
+                    "+crt-static" | "crt-static" => true,
+                    "-crt-static" => false,
+                    _ => enabled,
+                }),
+        };
+
+        Detail {
+            name: "STATIC_CRT".into(),
+            value_type: "bool".into(),
+            value: Some(enabled),
+        }
+    }
+}
+
+struct PanicStrategy;
+
+impl PanicStrategy {
+    pub fn new(fallback: &Option<String>) -> Detail<BuildEnv> {
+        let strategy = RustFlags::from_env()
+            .codegen("panic")
+            .map(str::to_owned)
+            .or_else(|| fallback.clone());
+
+        BuildEnv::with_value("PANIC_STRATEGY", strategy)
+    }
+}
+
+struct FeaturesString;
+
+impl FeaturesString {
+    pub fn new() -> Detail<BuildEnv> {
+        let mut features: Vec<_> = find_matching_vars("CARGO_FEATURE_").into_keys().collect();
+        features.sort();
+
+        BuildEnv::with_value("FEATURES_STRING", Some(features.join(",")))
+    }
+}
+
+struct AuthorCount;
+
+impl AuthorCount {
+    pub fn new() -> Detail<Option<u32>> {
+        let count = env::var("CARGO_PKG_AUTHORS")
+            .ok()
+            .map(|x| x.split(':').filter(|x| !x.trim().is_empty()).count() as u32);
+
+        Detail {
+            name: "AUTHOR_COUNT".into(),
+            value_type: "u32".into(),
+            value: count,
+        }
+    }
+}
+
+struct PackageList;
+
+impl PackageList {
+    pub fn new(name: &'static str, key: &str) -> Detail<Option<BuildEnvList>> {
+        let list = env::var_os("CARGO_MANIFEST_DIR")
+            .and_then(|dir| manifest::string_array(Path::new(&dir), "package", key));
+
+        Detail {
+            name: name.into(),
+            value_type: LIST_TYPE.into(),
+            value: list.map(BuildEnvList),
+        }
+    }
+}
+
+struct FeatureFlags;
+
+impl FeatureFlags {
+    /// The features declared in the manifest, sorted.
+    fn declared() -> Option<Vec<String>> {
+        let dir = env::var_os("CARGO_MANIFEST_DIR")?;
+        let mut features = manifest::table_keys(Path::new(&dir), "features")?;
+        features.sort();
+        Some(features)
+    }
+
+    pub fn available() -> Detail<Option<BuildEnvList>> {
+        Detail {
+            name: "FEATURES_AVAILABLE".into(),
+            value_type: LIST_TYPE.into(),
+            value: Self::declared().map(BuildEnvList),
+        }
+    }
+
+    pub fn enabled() -> Detail<Option<BuildEnvList>> {
+        let enabled = find_matching_vars("CARGO_FEATURE_");
+
+        let features = Self::declared().map(|declared| {
+            declared
+                .into_iter()
+                .filter(|x| enabled.contains_key(&x.to_uppercase().replace('-', "_")))
+                .collect()
+        });
+
+        Detail {
+            name: "FEATURES_ENABLED".into(),
+            value_type: LIST_TYPE.into(),
+            value: features.map(BuildEnvList),
+        }
+    }
+}
+
+struct OptionalDeps;
+
+impl OptionalDeps {
+    pub fn new() -> Detail<Option<BuildEnvList>> {
+        let enabled = find_matching_vars("CARGO_FEATURE_");
+
+        let deps = env::var_os("CARGO_MANIFEST_DIR")
+            .and_then(|dir| manifest::optional_dependencies(Path::new(&dir)))
+            .map(|deps| {
+                deps.into_iter()
+                    .filter(|x| enabled.contains_key(&x.to_uppercase().replace('-', "_")))
+                    .collect()
+            });
+
+        Detail {
+            name: "OPTIONAL_DEPS_ENABLED".into(),
+            value_type: LIST_TYPE.into(),
+            value: deps.map(BuildEnvList),
+        }
+    }
+}
+
+struct GitStashCount;
+
+impl GitStashCount {
+    pub fn new() -> Detail<Option<u32>> {
+        let count = git::git(&["stash", "list"]).map(|x| x.lines().count() as u32);
+
+        Detail {
+            name: "GIT_STASH_COUNT".into(),
+            value_type: "u32".into(),
+            value: count,
+        }
+    }
+}
+
+struct Git;
+
+impl Git {
+    pub fn new(name: &'static str, args: &[&str]) -> Detail<BuildEnv> {
+        BuildEnv::with_value(name, git::git(args))
+    }
+}
+
+struct SmartVersion;
+
+impl SmartVersion {
+    pub fn new(template: &str) -> Detail<BuildEnv> {
+        let version = env::var("CARGO_PKG_VERSION").ok().map(|version| {
+            let short = match git::git(&["rev-parse", "--short", "HEAD"]) {
+                Some(x) => x,
+                None => return version,
+            };
+
+            let tagged = git::git(&["describe", "--tags", "--exact-match", "HEAD"]).is_some();
+            let dirty = git::git(&["status", "--porcelain", "--untracked-files=no"])
+                .is_some_and(|x| !x.is_empty());
+
+            if tagged && !dirty {
+                return version;
             }
-        })
+
+            template
+                .replace("{version}", &version)
+                .replace("{git_short}", &short)
+                .replace("{dirty}", if dirty { "-dirty" } else { "" })
+        });
+
+        BuildEnv::with_value("SMART_VERSION", version)
+    }
+}
+
+struct Env(&'static str);
+
+impl Render for Env {
+    fn render_option(&self) -> Result<String> {
+        Ok(format!("option_env!(\"{}\")", self.0))
+    }
+
+    fn render(&self) -> Result<String> {
+        Ok(format!("env!(\"{}\")", self.0))
+    }
+
+    fn render_placeholder(&self, placeholder: &str) -> Result<String> {
+        Ok(format!(
+            "Some(match option_env!(\"{}\") {{ Some(x) => x, None => {:?} }})",
+            self.0, placeholder
+        ))
+    }
+
+    fn render_wrapped(&self, wrapper: &OptionalWrapper) -> Result<String> {
+        Ok(format!(
+            "match option_env!(\"{}\") {{ Some(x) => {}, None => {} }}",
+            self.0,
+            wrapper.some("x"),
+            wrapper.none()
+        ))
+    }
+
+    fn resolve(&self) -> Option<String> {
+        env::var(self.0).ok()
+    }
+
+    fn missing(&self) -> bool {
+        env::var_os(self.0).is_none()
+    }
+}
+
+impl Env {
+    pub fn new(name: &'static str, env: &'static str) -> Detail<Env> {
+        Detail {
+            name: name.into(),
+            value_type: STR_TYPE.into(),
+            value: Env(env),
+        }
+    }
+}
+
+struct BuildEnv(Option<String>);
+
+impl Render for BuildEnv {
+    fn render_option(&self) -> Result<String> {
+        match self.0 {
+            Some(ref x) => Ok(format!("Some({:?})", x)),
+            None => Ok("None".to_owned()),
+        }
+    }
+
+    fn render(&self) -> Result<String> {
+        match self.0 {
+            Some(ref x) => Ok(format!("{:?}", x)),
+            None => Err(Error::Missing),
+        }
+    }
+
+    fn resolve(&self) -> Option<String> {
+        self.0.clone()
+    }
+}
+
+impl BuildEnv {
+    pub fn new(name: &'static str, env: &'static str) -> Detail<Self> {
+        Self::with_value(name, env::var(env).ok())
+    }
+
+    pub fn with_value<N>(name: N, value: Option<String>) -> Detail<Self>
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        Detail {
+            name: name.into(),
+            value_type: STR_TYPE.into(),
+            value: BuildEnv(value),
+        }
+    }
+}
+
+struct BuildBytes(Option<Vec<u8>>);
+
+impl BuildBytes {
+    /// A byte string literal, cast to a slice so it also fits in `Some(..)`.
+    fn literal(bytes: &[u8]) -> String {
+        let mut txt = String::from("b\"");
+
+        for byte in bytes {
+            match *byte {
+                b'\\' => txt.push_str("\\\\"),
+                b'"' => txt.push_str("\\\""),
+                x @ b' '..=b'~' => txt.push(x as char),
+                x => txt.push_str(&format!("\\x{:02x}", x)),
+            }
+        }
+
+        txt.push_str("\" as &[u8]");
+        txt
+    }
+}
+
+impl Render for BuildBytes {
+    fn render_option(&self) -> Result<String> {
+        match self.0 {
+            Some(ref x) => Ok(format!("Some({})", Self::literal(x))),
+            None => Ok("None".to_owned()),
+        }
+    }
+
+    fn render(&self) -> Result<String> {
+        match self.0 {
+            Some(ref x) => Ok(Self::literal(x)),
+            None => Err(Error::Missing),
+        }
+    }
+
+    fn resolve(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Parses the common spellings of a boolean, like `"1"`, `"yes"`, or `"false"`.
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" | "y" => Some(true),
+        "0" | "false" | "no" | "off" | "n" => Some(false),
+        _ => None,
+    }
+}
+
+fn find_matching_vars(prefix: &'static str) -> HashMap<String, String> {
+    env::vars()
+        .filter_map(|(k, v)| k.strip_prefix(prefix).map(|k| (k.to_owned(), v)))
         .collect()
 }
 
@@ -430,14 +4070,9 @@ struct BuildEnvList(Vec<String>);
 impl BuildEnvList {
     pub fn new(name: &'static str, prefix: &'static str) -> Detail<Self> {
         Detail {
-            name,
-            value_type: "&'static [&'static str]",
-            value: BuildEnvList(
-                find_matching_vars(prefix)
-                    .into_iter()
-                    .map(|(k, _)| k)
-                    .collect(),
-            ),
+            name: name.into(),
+            value_type: LIST_TYPE.into(),
+            value: BuildEnvList(find_matching_vars(prefix).into_keys().collect()),
         }
     }
 }
@@ -453,23 +4088,86 @@ impl Render for BuildEnvList {
         let mut txt = String::from("&[\n");
 
         for item in &self.0 {
-            write!(txt, "    {:?},\n", item)?;
+            writeln!(txt, "    {:?},", item)?;
         }
 
         write!(txt, "]")?;
 
         Ok(txt)
     }
+
+    fn resolve(&self) -> Option<String> {
+        Some(self.0.join(","))
+    }
+}
+
+impl Render for Option<BuildEnvList> {
+    fn render_option(&self) -> Result<String> {
+        match self {
+            Some(x) => x.render_option(),
+            None => Ok("None".to_owned()),
+        }
+    }
+
+    fn render(&self) -> Result<String> {
+        match self {
+            Some(x) => x.render(),
+            None => Err(Error::Missing),
+        }
+    }
+
+    fn resolve(&self) -> Option<String> {
+        self.as_ref().and_then(BuildEnvList::resolve)
+    }
+}
+
+/// The message of a caught panic, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(x) => (*x).to_owned(),
+        None => match payload.downcast_ref::<String>() {
+            Some(x) => x.clone(),
+            None => "unknown error".to_owned(),
+        },
+    }
 }
 
-struct BuildEnvMap(HashMap<String, String>);
+struct BuildEnvMap {
+    vars: Vec<(String, String)>,
+    phf_path: String,
+    ordered: bool,
+}
 
 impl BuildEnvMap {
-    pub fn new(name: &'static str, prefix: &'static str) -> Detail<Self> {
+    pub fn new(
+        name: &'static str,
+        prefix: &'static str,
+        phf_path: &str,
+        ordered: bool,
+    ) -> Detail<Self> {
+        let vars = find_matching_vars(prefix).into_iter().collect();
+        Self::with_entries(name, vars, phf_path, ordered)
+    }
+
+    pub fn with_entries<N>(
+        name: N,
+        vars: Vec<(String, String)>,
+        phf_path: &str,
+        ordered: bool,
+    ) -> Detail<Self>
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        let map = if ordered { "OrderedMap" } else { "Map" };
+
         Detail {
-            name,
-            value_type: "::phf::Map<&'static str, &'static str>",
-            value: BuildEnvMap(find_matching_vars(prefix)),
+            name: name.into(),
+            value_type: format!("{}::{}<&'static str, &'static str>", phf_path, map).into(),
+            value: BuildEnvMap {
+                vars,
+                phf_path: phf_path.to_owned(),
+                ordered,
+            },
         }
     }
 }
@@ -480,16 +4178,47 @@ impl Render for BuildEnvMap {
     }
 
     fn render(&self) -> Result<String> {
+        // `phf_codegen` panics on duplicate keys, so keep the last value of
+        // each, sorted by key.
+        let vars: BTreeMap<_, _> = self.vars.iter().cloned().collect();
+
         let mut txt = vec![];
 
-        let mut map = phf_codegen::Map::<&str>::new();
+        let built = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            if self.ordered {
+                let mut map = phf_codegen::OrderedMap::<&str>::new();
+                map.phf_path(&self.phf_path);
 
-        for (k, v) in &self.0 {
-            map.entry(k, &format!("{:?}", v));
-        }
+                for (k, v) in &vars {
+                    map.entry(k, &format!("{:?}", v));
+                }
+
+                map.build(&mut txt)
+            } else {
+                let mut map = phf_codegen::Map::<&str>::new();
+                map.phf_path(&self.phf_path);
 
-        map.build(&mut txt)?;
+                for (k, v) in &vars {
+                    map.entry(k, &format!("{:?}", v));
+                }
+
+                map.build(&mut txt)
+            }
+        }));
+
+        match built {
+            Ok(result) => result?,
+            Err(payload) => return Err(Error::PhfBuild(panic_message(&*payload))),
+        }
 
         Ok(String::from_utf8(txt).unwrap())
     }
+
+    fn resolve(&self) -> Option<String> {
+        None
+    }
+
+    fn plain(&self) -> bool {
+        false
+    }
 }
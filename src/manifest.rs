@@ -0,0 +1,420 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Inspection of `Cargo.toml` manifests.
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<PathBuf, Option<Rc<String>>>> = RefCell::new(HashMap::new());
+}
+
+/// Forgets every file read so far, so the next generation sees any changes.
+///
+/// Called once per generation, which then reads each manifest at most once
+/// and gives every detail the same view of it.
+pub fn clear_cache() {
+    CACHE.with(|x| x.borrow_mut().clear());
+}
+
+/// The contents of the file at `path`, like a `Cargo.toml` or `Cargo.lock`,
+/// or `None` if it can't be read.
+pub fn read(path: &Path) -> Option<Rc<String>> {
+    if let Some(cached) = CACHE.with(|x| x.borrow().get(path).cloned()) {
+        return cached;
+    }
+
+    let text = fs::read_to_string(path).ok().map(Rc::new);
+    CACHE.with(|x| x.borrow_mut().insert(path.to_path_buf(), text.clone()));
+    text
+}
+
+/// Whether the `Cargo.toml` in `dir` declares a `[workspace]` table.
+fn declares_workspace(dir: &Path) -> bool {
+    let text = match read(&dir.join("Cargo.toml")) {
+        Some(x) => x,
+        None => return false,
+    };
+
+    text.lines().any(|line| {
+        let line = line.trim();
+        line == "[workspace]" || line.starts_with("[workspace.")
+    })
+}
+
+/// Finds the root of the workspace containing the crate in `manifest_dir`.
+///
+/// This is the topmost ancestor whose `Cargo.toml` declares a `[workspace]`,
+/// or `manifest_dir` itself when there is no such ancestor.
+pub fn workspace_root(manifest_dir: &Path) -> PathBuf {
+    manifest_dir
+        .ancestors()
+        .filter(|dir| declares_workspace(dir))
+        .last()
+        .unwrap_or(manifest_dir)
+        .to_path_buf()
+}
+
+/// The string value of `key` in the `[table]` of the `Cargo.toml` in `dir`.
+///
+/// This only understands `key = "value"` lines, which is how the keys read
+/// here are almost always written.
+fn string_value(dir: &Path, table: &str, key: &str) -> Option<String> {
+    let value = raw_value(&dir.join("Cargo.toml"), table, key)?;
+    unquote(&value).map(str::to_owned)
+}
+
+/// The value of `key` in the `[table]` of the TOML file at `path`, like a
+/// `Cargo.toml` or `.cargo/config.toml`, with quotes removed from strings.
+///
+/// Like [`string_value`], this only understands `key = value` lines, so
+/// dotted keys and inline tables aren't found.
+pub fn value(path: &Path, table: &str, key: &str) -> Option<String> {
+    let value = raw_value(path, table, key)?;
+
+    match unquote(&value) {
+        Some(x) => Some(x.to_owned()),
+        None => Some(value),
+    }
+}
+
+/// The contents of `value` if it's a quoted string.
+fn unquote(value: &str) -> Option<&str> {
+    if value.len() >= 2 && (value.starts_with('"') || value.starts_with('\'')) {
+        return Some(&value[1..value.len() - 1]);
+    }
+
+    None
+}
+
+/// The value of `key` in the `[table]` of the TOML file at `path`, as
+/// written, without any trailing comment.
+fn raw_value(path: &Path, table: &str, key: &str) -> Option<String> {
+    let text = read(path)?;
+    let header = format!("[{}]", table);
+    let mut in_table = false;
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_table = line == header;
+            continue;
+        }
+
+        if !in_table {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+
+        if parts.next().map(str::trim) != Some(key) {
+            continue;
+        }
+
+        return Some(parts.next()?.split('#').next()?.trim().to_owned());
+    }
+
+    None
+}
+
+/// The dependency resolver used for the workspace at `root`, like `"2"`.
+///
+/// Like Cargo, this is the `resolver` of the root manifest's `[workspace]` or
+/// `[package]`, defaulting to the one implied by the root package's edition,
+/// or `"1"` for virtual workspaces. Returns `None` when `root` has no
+/// manifest.
+pub fn resolver(root: &Path) -> Option<String> {
+    read(&root.join("Cargo.toml"))?;
+
+    let explicit = string_value(root, "workspace", "resolver")
+        .or_else(|| string_value(root, "package", "resolver"));
+
+    if explicit.is_some() {
+        return explicit;
+    }
+
+    let edition = string_value(root, "package", "edition")
+        .or_else(|| string_value(root, "workspace.package", "edition"));
+
+    let resolver = match edition.as_deref() {
+        Some("2021") => "2",
+        Some("2024") => "3",
+        _ => "1",
+    };
+
+    Some(resolver.to_owned())
+}
+
+/// The keys of the `[table]` of the `Cargo.toml` in `dir`, in order, or
+/// `None` when there's no manifest. A missing table has no keys.
+///
+/// Values spanning several lines, like long arrays, are skipped over.
+pub fn table_keys(dir: &Path, table: &str) -> Option<Vec<String>> {
+    let text = read(&dir.join("Cargo.toml"))?;
+    let header = format!("[{}]", table);
+    let mut in_table = false;
+    let mut depth = 0i32;
+    let mut keys = vec![];
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if depth > 0 {
+            depth += brackets(line);
+            continue;
+        }
+
+        if line.starts_with('[') {
+            in_table = line == header;
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+
+        if let Some(value) = parts.next() {
+            depth = brackets(value);
+
+            if in_table {
+                keys.push(key.trim_matches(|c| c == '"' || c == '\'').to_owned());
+            }
+        }
+    }
+
+    Some(keys)
+}
+
+/// How many packages are in the workspace at `root`, or `None` when `root`
+/// has no manifest. A package that isn't in a workspace counts as one.
+///
+/// Members are listed in the `members` of the root's `[workspace]`, where
+/// path components may contain `*` wildcards, less those in `exclude`. The
+/// root package is a member too, and directories without a manifest are
+/// skipped.
+pub fn member_count(root: &Path) -> Option<u32> {
+    let text = read(&root.join("Cargo.toml"))?;
+
+    if !declares_workspace(root) {
+        return Some(1);
+    }
+
+    let expand = |key| {
+        string_array(root, "workspace", key)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|pattern| expand_glob(root, pattern))
+            .collect::<Vec<_>>()
+    };
+
+    let excluded = expand("exclude");
+    let mut members: Vec<_> = expand("members")
+        .into_iter()
+        .filter(|dir| !excluded.contains(dir) && dir.join("Cargo.toml").is_file())
+        .collect();
+
+    if text.lines().any(|line| line.trim() == "[package]") {
+        members.push(root.to_path_buf());
+    }
+
+    members.sort();
+    members.dedup();
+
+    Some(members.len() as u32)
+}
+
+/// The directories under `root` matching `pattern`, whose components are
+/// separated by `/` and may contain `*` wildcards.
+fn expand_glob(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+
+    for component in pattern.split('/').filter(|x| !x.is_empty() && *x != ".") {
+        if !component.contains('*') {
+            dirs = dirs.into_iter().map(|dir| dir.join(component)).collect();
+            continue;
+        }
+
+        let mut matched = vec![];
+
+        for dir in dirs {
+            let entries = match fs::read_dir(&dir) {
+                Ok(x) => x,
+                Err(_) => continue,
+            };
+
+            for entry in entries.filter_map(|x| x.ok()) {
+                let name = entry.file_name();
+
+                if entry.path().is_dir() && wildcard(component, &name.to_string_lossy()) {
+                    matched.push(entry.path());
+                }
+            }
+        }
+
+        dirs = matched;
+    }
+
+    dirs
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any characters.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == name,
+        Some(star) => {
+            let (prefix, rest) = (&pattern[..star], &pattern[star + 1..]);
+
+            name.starts_with(prefix)
+                && (prefix.len()..=name.len())
+                    .filter(|x| name.is_char_boundary(*x))
+                    .any(|x| wildcard(rest, &name[x..]))
+        }
+    }
+}
+
+/// The strings in the array value of `key` in the `[table]` of the
+/// `Cargo.toml` in `dir`, like `keywords = ["cli", "build"]`, or `None` when
+/// there's no manifest. A missing key has no strings.
+///
+/// The array may span several lines. Values inherited with
+/// `key.workspace = true` aren't followed.
+pub fn string_array(dir: &Path, table: &str, key: &str) -> Option<Vec<String>> {
+    let text = read(&dir.join("Cargo.toml"))?;
+    let header = format!("[{}]", table);
+    let mut in_table = false;
+    let mut value = None::<String>;
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if let Some(ref mut value) = value {
+            value.push_str(line);
+
+            if brackets(value) <= 0 {
+                break;
+            }
+
+            continue;
+        }
+
+        if line.starts_with('[') {
+            in_table = line == header;
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+
+        if in_table && parts.next().map(str::trim) == Some(key) {
+            let rest = parts.next().unwrap_or("").trim().to_owned();
+            let done = brackets(&rest) <= 0;
+            value = Some(rest);
+
+            if done {
+                break;
+            }
+        }
+    }
+
+    Some(value.map(|x| quoted(&x)).unwrap_or_default())
+}
+
+/// The optional dependencies of the `Cargo.toml` in `dir`, sorted and named
+/// like their implicit features, or `None` when there's no manifest.
+///
+/// Dependencies are found in `[dependencies]` and the
+/// `[target.'...'.dependencies]` tables, either with an inline table like
+/// `foo = { version = "1", optional = true }`, or in a table of their own
+/// like `[dependencies.foo]`. Dotted keys like `foo.optional = true` aren't
+/// understood.
+pub fn optional_dependencies(dir: &Path) -> Option<Vec<String>> {
+    let text = read(&dir.join("Cargo.toml"))?;
+    let mut deps = BTreeSet::new();
+    let mut in_deps = false;
+    let mut table_dep = None;
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if line.starts_with('[') {
+            let header = line.trim_matches(|c| c == '[' || c == ']');
+            in_deps = header == "dependencies"
+                || (header.starts_with("target.") && header.ends_with(".dependencies"));
+            table_dep = dependency_table(header);
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+
+        let value: String = match parts.next() {
+            Some(value) => value.split_whitespace().collect(),
+            None => continue,
+        };
+
+        if in_deps && value.contains("optional=true") {
+            deps.insert(key.trim_matches(|c| c == '"' || c == '\'').to_owned());
+        } else if let Some(ref name) = table_dep {
+            if key == "optional" && value == "true" {
+                deps.insert(name.clone());
+            }
+        }
+    }
+
+    Some(deps.into_iter().collect())
+}
+
+/// The dependency a table like `[dependencies.foo]` is for, if it's one.
+fn dependency_table(header: &str) -> Option<String> {
+    const TABLE: &str = ".dependencies.";
+
+    let name = if let Some(name) = header.strip_prefix("dependencies.") {
+        name
+    } else if header.starts_with("target.") {
+        &header[header.rfind(TABLE)? + TABLE.len()..]
+    } else {
+        return None;
+    };
+
+    Some(name.trim_matches(|c| c == '"' || c == '\'').to_owned())
+}
+
+/// The contents of every quoted string in `text`.
+fn quoted(text: &str) -> Vec<String> {
+    let mut strings = vec![];
+    let mut current = None::<(char, String)>;
+
+    for c in text.chars() {
+        match current {
+            Some((quote, ref mut string)) if c != quote => string.push(c),
+            Some((_, ref mut string)) => {
+                strings.push(::std::mem::take(string));
+                current = None;
+            }
+            None if c == '"' || c == '\'' => current = Some((c, String::new())),
+            None => (),
+        }
+    }
+
+    strings
+}
+
+/// How many more `[` than `]` there are in `text`.
+fn brackets(text: &str) -> i32 {
+    text.chars().fold(0, |depth, c| match c {
+        '[' => depth + 1,
+        ']' => depth - 1,
+        _ => depth,
+    })
+}
+
+/// Path of the `Cargo.lock` of the workspace containing the crate being built.
+pub fn lockfile() -> Option<PathBuf> {
+    let dir = env::var_os("CARGO_MANIFEST_DIR")?;
+    Some(workspace_root(Path::new(&dir)).join("Cargo.lock"))
+}
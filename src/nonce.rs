@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Random tokens that differ between builds.
+
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A SplitMix64 generator, which is plenty for tokens that only need to
+/// differ, not to be unpredictable.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// 128 random bits as 32 lowercase hex digits, seeded by the current time and
+/// process ID.
+pub fn generate() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs() ^ u64::from(x.subsec_nanos()).rotate_left(32))
+        .unwrap_or(0);
+
+    let mut rng = SplitMix64(nanos ^ u64::from(process::id()).rotate_left(17));
+
+    format!("{:016x}{:016x}", rng.next(), rng.next())
+}
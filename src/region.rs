@@ -0,0 +1,97 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Splicing build details into a region of an existing file.
+
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use super::BuildDetails;
+use counter::sibling;
+use error::{Error, Result};
+
+impl BuildDetails {
+    /// Replaces the lines between `begin_marker` and `end_marker` in the
+    /// existing file at `path` with freshly generated details, leaving the
+    /// rest of the file, including the marker lines, intact.
+    ///
+    /// This allows committing the build details into a source file next to
+    /// hand-written code, instead of using `include!`. Each marker must appear
+    /// on exactly one line, with `begin_marker` first, or
+    /// [`Error::InvalidRegion`] is returned. The file is replaced atomically.
+    ///
+    /// ```no_run
+    /// # use build_details::BuildDetails;
+    /// BuildDetails::default()
+    ///     .generate_region(
+    ///         "src/build_info.rs",
+    ///         "// BEGIN build_details",
+    ///         "// END build_details",
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn generate_region<P: AsRef<Path>>(
+        &self,
+        path: P,
+        begin_marker: &str,
+        end_marker: &str,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        let mut generated = vec![];
+        self.write_to(&mut generated)?;
+        let generated = String::from_utf8(generated).expect("generated code is UTF-8");
+
+        let spliced = splice(&text, begin_marker, end_marker, &generated)?;
+
+        let tmp = sibling(path, &format!(".{}.tmp", process::id()));
+        fs::write(&tmp, spliced)?;
+        fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+/// Byte offset of the start of the only line of `text` containing `marker`.
+fn find_line(text: &str, marker: &str) -> Result<usize> {
+    let mut found = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        if line.contains(marker) {
+            if found.is_some() {
+                return Err(Error::InvalidRegion(marker.to_owned()));
+            }
+
+            found = Some(offset);
+        }
+
+        offset += line.len();
+    }
+
+    found.ok_or_else(|| Error::InvalidRegion(marker.to_owned()))
+}
+
+fn splice(text: &str, begin_marker: &str, end_marker: &str, generated: &str) -> Result<String> {
+    let begin = find_line(text, begin_marker)?;
+    let end = find_line(text, end_marker)?;
+
+    let body = match text[begin..].find('\n') {
+        Some(x) => begin + x + 1,
+        None => return Err(Error::InvalidRegion(end_marker.to_owned())),
+    };
+
+    if end < body {
+        return Err(Error::InvalidRegion(end_marker.to_owned()));
+    }
+
+    let mut spliced = String::with_capacity(text.len() + generated.len());
+    spliced.push_str(&text[..body]);
+    spliced.push_str(generated);
+    spliced.push_str(&text[end..]);
+
+    Ok(spliced)
+}
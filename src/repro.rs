@@ -0,0 +1,185 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Auditing a configuration for reproducible builds.
+
+use std::env;
+use std::fmt;
+
+use super::{BuildDetail, BuildDetails, Extra};
+
+/// A selected [`BuildDetail`] that can differ between two builds of the same
+/// source.
+#[derive(Debug, Clone)]
+pub struct ReproIssue {
+    detail: BuildDetail,
+    reason: &'static str,
+}
+
+impl ReproIssue {
+    /// The detail introducing the non-determinism.
+    pub fn detail(&self) -> BuildDetail {
+        self.detail
+    }
+
+    /// A short explanation of why the detail isn't reproducible.
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+}
+
+/// A generated item that isn't a [`BuildDetail`], like
+/// `BUILD_DETAILS_GEN_MS`, but can differ between two builds of the same
+/// source.
+#[derive(Debug, Clone)]
+pub struct ReproOutput {
+    name: &'static str,
+    reason: &'static str,
+}
+
+impl ReproOutput {
+    /// The name of the generated item.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// A short explanation of why the item isn't reproducible.
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+}
+
+/// The result of [`BuildDetails::reproducibility_report`].
+#[derive(Debug, Clone)]
+pub struct ReproReport {
+    issues: Vec<ReproIssue>,
+    outputs: Vec<ReproOutput>,
+}
+
+impl ReproReport {
+    /// Whether none of the selected details or other generated items
+    /// introduce non-determinism.
+    pub fn is_reproducible(&self) -> bool {
+        self.issues.is_empty() && self.outputs.is_empty()
+    }
+
+    /// Every selected detail that isn't reproducible.
+    pub fn issues(&self) -> &[ReproIssue] {
+        &self.issues
+    }
+
+    /// Every other generated item that isn't reproducible.
+    pub fn outputs(&self) -> &[ReproOutput] {
+        &self.outputs
+    }
+}
+
+impl fmt::Display for ReproReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_reproducible() {
+            return write!(f, "all selected details are reproducible");
+        }
+
+        let issues = self
+            .issues
+            .iter()
+            .map(|x| (format!("{:?}", x.detail), x.reason));
+        let outputs = self.outputs.iter().map(|x| (x.name.to_owned(), x.reason));
+
+        for (idx, (name, reason)) in issues.chain(outputs).enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}: {}", name, reason)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BuildDetail {
+    /// Why this detail can differ between builds of the same source, if it
+    /// can.
+    fn nondeterminism(self) -> Option<&'static str> {
+        use self::BuildDetail::*;
+
+        match self {
+            Timestamp if env::var_os("SOURCE_DATE_EPOCH").is_none() => {
+                Some("wall-clock time of the build, unless SOURCE_DATE_EPOCH is set")
+            }
+            BuildDateLocal
+                if env::var_os("SOURCE_DATE_EPOCH").is_none() || env::var_os("TZ").is_none() =>
+            {
+                Some("local time of the build, unless SOURCE_DATE_EPOCH and TZ are set")
+            }
+            BuildTzOffset if env::var_os("TZ").is_none() => {
+                Some("timezone of the machine running the build, unless TZ is set")
+            }
+            WorkspaceRoot => Some("embeds the local path of the workspace"),
+            BuildCwd => Some("embeds the local working directory of build.rs"),
+            BuildHostOs => Some("depends on the machine running the build"),
+            GitWorktree => Some("embeds the local path of the git checkout"),
+            OutDir => Some("embeds the local path of the build's output directory"),
+            BuildNonce => Some("random for every build"),
+            BuildLocale => Some("depends on the locale of the machine running the build"),
+            BuildShell => Some("depends on the shell of the user running the build"),
+            RerunInputs => Some("can embed local paths of the build's inputs"),
+            _ => None,
+        }
+    }
+}
+
+impl Extra {
+    /// The generated item that can differ between builds of the same source,
+    /// if there is one.
+    fn nondeterminism(&self) -> Option<ReproOutput> {
+        match self {
+            Extra::BuildNumber(_) => Some(ReproOutput {
+                name: "BUILD_NUMBER",
+                reason: "incremented by every build",
+            }),
+            Extra::BinarySize(_) => Some(ReproOutput {
+                name: "PREV_BINARY_SIZE",
+                reason: "size of the binary left by a previous build",
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl BuildDetails {
+    /// Lists the selected details, and other enabled items, that introduce
+    /// non-determinism, so a configuration can be audited for reproducible
+    /// builds.
+    ///
+    /// This doesn't generate anything.
+    pub fn reproducibility_report(&self) -> ReproReport {
+        let mut issues: Vec<_> = self
+            .selected()
+            .filter_map(|(detail, _)| {
+                detail
+                    .nondeterminism()
+                    .map(|reason| ReproIssue { detail, reason })
+            })
+            .collect();
+
+        issues.sort_by_key(|x| format!("{:?}", x.detail));
+
+        let mut outputs: Vec<_> = self
+            .extras
+            .iter()
+            .filter_map(Extra::nondeterminism)
+            .collect();
+
+        if self.gen_timing {
+            outputs.push(ReproOutput {
+                name: "BUILD_DETAILS_GEN_MS",
+                reason: "time taken to generate the details",
+            });
+        }
+
+        ReproReport { issues, outputs }
+    }
+}
@@ -0,0 +1,100 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Support for using generated constants at runtime, with
+//! [`build_details_map!`](../macro.build_details_map.html).
+//!
+//! Using these requires `build_details` as a regular dependency, in addition
+//! to the build dependency.
+
+/// A generated value that can be displayed as a string.
+pub trait DetailValue {
+    /// The value as a string, or `None` for a missing optional value.
+    fn detail_string(&self) -> Option<String>;
+}
+
+impl DetailValue for &str {
+    fn detail_string(&self) -> Option<String> {
+        Some((*self).to_owned())
+    }
+}
+
+impl DetailValue for &[&str] {
+    fn detail_string(&self) -> Option<String> {
+        Some(self.join(","))
+    }
+}
+
+impl DetailValue for &[u8] {
+    fn detail_string(&self) -> Option<String> {
+        Some(String::from_utf8_lossy(self).into_owned())
+    }
+}
+
+macro_rules! display_value {
+    ($($ty:ty),*) => {
+        $(
+            impl DetailValue for $ty {
+                fn detail_string(&self) -> Option<String> {
+                    Some(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+display_value!(bool, u8, u32, u64, i32, i64);
+
+impl<T> DetailValue for Option<T>
+where
+    T: DetailValue,
+{
+    fn detail_string(&self) -> Option<String> {
+        self.as_ref().and_then(DetailValue::detail_string)
+    }
+}
+
+/// Collects generated constants into a `Vec<(&'static str, String)>` of
+/// their names and values, at runtime.
+///
+/// List the constants to collect, which must be in scope. Missing optional
+/// values are left out, and maps like `CFG` aren't supported.
+///
+/// A macro can't check whether a name exists, so listing a detail that
+/// wasn't selected in `build.rs` is a compile error. Details added with
+/// [`include`](../struct.BuildDetails.html#method.include) are always
+/// generated, as `None` when unavailable, so list those instead of ones that
+/// might be excluded.
+///
+/// ```ignore
+/// #[macro_use]
+/// extern crate build_details;
+///
+/// mod build_details_generated {
+///     include!(concat!(env!("OUT_DIR"), "/build_details.rs"));
+/// }
+///
+/// fn main() {
+///     use build_details_generated::*;
+///
+///     for (name, value) in build_details_map!(VERSION, PROFILE) {
+///         println!("{}: {}", name, value);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! build_details_map {
+    ($($name:ident),* $(,)?) => {{
+        let mut map: ::std::vec::Vec<(&'static str, ::std::string::String)> =
+            ::std::vec::Vec::new();
+
+        $(
+            if let Some(value) = $crate::runtime::DetailValue::detail_string(&$name) {
+                map.push((stringify!($name), value));
+            }
+        )*
+
+        map
+    }};
+}
@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Querying the `rustc` building the crate.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::process::Command;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<Vec<String>, Option<String>>> = RefCell::new(HashMap::new());
+}
+
+/// Runs the compiler Cargo builds with (`RUSTC`, or `rustc` outside of Cargo)
+/// with `args`, returning its output with surrounding whitespace removed.
+///
+/// The output is cached, so details asking the same question only run the
+/// compiler once. Returns `None` if the compiler can't be run, exits
+/// unsuccessfully (for example on an unknown flag), or prints something that
+/// isn't UTF-8.
+pub fn rustc(args: &[&str]) -> Option<String> {
+    let key: Vec<_> = args.iter().map(|x| (*x).to_owned()).collect();
+
+    if let Some(cached) = CACHE.with(|x| x.borrow().get(&key).cloned()) {
+        return cached;
+    }
+
+    let output = run(args);
+    CACHE.with(|x| x.borrow_mut().insert(key, output.clone()));
+    output
+}
+
+fn run(args: &[&str]) -> Option<String> {
+    let program = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(program).args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+
+    Some(stdout.trim().to_owned())
+}
+
+/// Value of the `key: value` line in `rustc -vV`.
+pub fn verbose_version(key: &str) -> Option<String> {
+    let version = rustc(&["-vV"])?;
+
+    version
+        .lines()
+        .filter_map(|line| line.strip_prefix(key))
+        .filter_map(|line| line.strip_prefix(": "))
+        .map(str::to_owned)
+        .next()
+}
+
+/// The target `rustc` builds for when none is given, from
+/// `rustc --print host-tuple`, or the `host` line of `rustc -vV` on compilers
+/// without that flag.
+pub fn default_target() -> Option<String> {
+    rustc(&["--print", "host-tuple"]).or_else(|| verbose_version("host"))
+}
@@ -0,0 +1,84 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Parsing of the extra flags Cargo passes along to `rustc`.
+
+use std::env;
+
+/// The custom flags for `rustc`, split into individual arguments.
+///
+/// Cargo provides `CARGO_ENCODED_RUSTFLAGS` (separated by `0x1f`) to build
+/// scripts. When that isn't available, `RUSTFLAGS` is split on whitespace.
+pub struct RustFlags(Vec<String>);
+
+impl RustFlags {
+    pub fn from_env() -> Self {
+        if let Ok(encoded) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+            if !encoded.is_empty() {
+                return RustFlags(encoded.split('\x1f').map(str::to_owned).collect());
+            }
+        }
+
+        let flags = env::var("RUSTFLAGS").unwrap_or_default();
+        RustFlags(flags.split_whitespace().map(str::to_owned).collect())
+    }
+
+    /// Every argument given to a flag spelled `short` (like `-C`) or, if it
+    /// has one, `long` (like `--codegen`), in the order they appear.
+    fn arguments<'a>(&'a self, short: &str, long: Option<&str>) -> Vec<&'a str> {
+        let mut args = vec![];
+        let mut iter = self.0.iter();
+
+        while let Some(flag) = iter.next() {
+            let long_arg = long.and_then(|long| flag.strip_prefix(long));
+
+            if flag == short || long_arg == Some("") {
+                if let Some(arg) = iter.next() {
+                    args.push(arg.as_str());
+                }
+            } else if let Some(arg) = long_arg {
+                if let Some(arg) = arg.strip_prefix('=') {
+                    args.push(arg);
+                }
+            } else if let Some(arg) = flag.strip_prefix(short) {
+                args.push(arg);
+            }
+        }
+
+        args
+    }
+
+    /// Value of the last `-C key=value` (or `--codegen key=value`) option, which
+    /// is the one `rustc` honours.
+    pub fn codegen(&self, key: &str) -> Option<&str> {
+        self.codegen_all(key).into_iter().next_back()
+    }
+
+    /// Every value of `-C key=value` (or `--codegen key=value`) options, in
+    /// the order they appear.
+    pub fn codegen_all(&self, key: &str) -> Vec<&str> {
+        self.arguments("-C", Some("--codegen"))
+            .into_iter()
+            .filter_map(|arg| split_option(arg, key))
+            .collect()
+    }
+
+    /// Every value of `-Z key=value` options, in the order they appear.
+    pub fn unstable(&self, key: &str) -> Vec<&str> {
+        self.arguments("-Z", None)
+            .into_iter()
+            .filter_map(|arg| split_option(arg, key))
+            .collect()
+    }
+}
+
+fn split_option<'a>(arg: &'a str, key: &str) -> Option<&'a str> {
+    let mut parts = arg.splitn(2, '=');
+
+    if parts.next() == Some(key) {
+        Some(parts.next().unwrap_or(""))
+    } else {
+        None
+    }
+}
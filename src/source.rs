@@ -0,0 +1,53 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Walking the crate's Rust sources.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The `src` directory of the crate being built.
+pub fn dir() -> Option<PathBuf> {
+    env::var_os("CARGO_MANIFEST_DIR").map(|x| Path::new(&x).join("src"))
+}
+
+/// Every `.rs` file under `dir`, sorted by path so the order is stable.
+pub fn rust_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|x| x == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Total number of lines in the `.rs` files under `dir`.
+pub fn lines_of_code(dir: &Path) -> io::Result<u64> {
+    let mut total = 0;
+
+    for file in rust_files(dir)? {
+        let bytes = fs::read(file)?;
+        let newlines = bytes.iter().filter(|x| **x == b'\n').count();
+        let unterminated = !bytes.is_empty() && !bytes.ends_with(b"\n");
+
+        total += (newlines + usize::from(unterminated)) as u64;
+    }
+
+    Ok(total)
+}
@@ -0,0 +1,230 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Checking vendored dependencies against the checksums `cargo vendor`
+//! records.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha256;
+
+/// The directory holding vendored sources for the workspace at `root`.
+///
+/// This is the `directory` of a `[source.*]` table in `.cargo/config.toml`
+/// (or `.cargo/config`), or `vendor` when none is configured.
+pub fn dir(root: &Path) -> PathBuf {
+    for name in &["config.toml", "config"] {
+        let text = match fs::read_to_string(root.join(".cargo").join(name)) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        let mut in_source = false;
+
+        for line in text.lines() {
+            let line = line.trim();
+
+            if line.starts_with('[') {
+                in_source = line.starts_with("[source.");
+                continue;
+            }
+
+            if !in_source {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+
+            if parts.next().map(str::trim) != Some("directory") {
+                continue;
+            }
+
+            if let Some(value) = parts.next() {
+                return root.join(value.trim().trim_matches(|c| c == '"' || c == '\''));
+            }
+        }
+    }
+
+    root.join("vendor")
+}
+
+/// Whether any file in the vendored crates under `dir` differs from the
+/// checksum recorded in its crate's `.cargo-checksum.json`.
+///
+/// Returns `None` when `dir` doesn't exist or a checksum file can't be read.
+pub fn dirty(dir: &Path) -> Option<bool> {
+    for entry in fs::read_dir(dir).ok()? {
+        let krate = entry.ok()?.path();
+        let checksums = krate.join(".cargo-checksum.json");
+
+        if !checksums.is_file() {
+            continue;
+        }
+
+        let text = fs::read_to_string(&checksums).ok()?;
+
+        for (file, expected) in parse_files(&text)? {
+            match sha256::file(&krate.join(file)) {
+                Ok(ref actual) if *actual == expected => (),
+                _ => return Some(true),
+            }
+        }
+    }
+
+    Some(false)
+}
+
+/// Reads the `"files"` object of a `.cargo-checksum.json`, as pairs of path
+/// and hexadecimal SHA-256.
+fn parse_files(text: &str) -> Option<Vec<(String, String)>> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        pos: 0,
+    };
+
+    let mut files = None;
+
+    parser.expect(b'{')?;
+
+    loop {
+        let key = parser.string()?;
+        parser.expect(b':')?;
+
+        if key == "files" {
+            let mut pairs = vec![];
+
+            parser.expect(b'{')?;
+
+            if !parser.eat(b'}') {
+                loop {
+                    let file = parser.string()?;
+                    parser.expect(b':')?;
+                    pairs.push((file, parser.string()?));
+
+                    if parser.eat(b'}') {
+                        break;
+                    }
+
+                    parser.expect(b',')?;
+                }
+            }
+
+            files = Some(pairs);
+        } else {
+            parser.skip_scalar()?;
+        }
+
+        if parser.eat(b'}') {
+            return files;
+        }
+
+        parser.expect(b',')?;
+    }
+}
+
+/// Just enough of a JSON parser for `.cargo-checksum.json`.
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.text.len() && self.text[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+
+        if self.text.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        if self.eat(c) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Skips a string or a literal like `null`.
+    fn skip_scalar(&mut self) -> Option<()> {
+        self.skip_whitespace();
+
+        if self.text.get(self.pos) == Some(&b'"') {
+            return self.string().map(|_| ());
+        }
+
+        while self.pos < self.text.len() && self.text[self.pos].is_ascii_alphanumeric() {
+            self.pos += 1;
+        }
+
+        Some(())
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+
+        let mut bytes = vec![];
+
+        loop {
+            let c = *self.text.get(self.pos)?;
+            self.pos += 1;
+
+            match c {
+                b'"' => return String::from_utf8(bytes).ok(),
+                b'\\' => {
+                    let escaped = *self.text.get(self.pos)?;
+                    self.pos += 1;
+
+                    let c = match escaped {
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        x => x as char,
+                    };
+
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                x => bytes.push(x),
+            }
+        }
+    }
+
+    /// Decodes the `XXXX` after `\u`, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+
+        if !(0xd800..0xdc00).contains(&high) {
+            return ::std::char::from_u32(high);
+        }
+
+        if self.text.get(self.pos..self.pos + 2)? != b"\\u" {
+            return None;
+        }
+        self.pos += 2;
+
+        let low = self.hex4()?;
+        ::std::char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low.checked_sub(0xdc00)?))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+
+        u32::from_str_radix(::std::str::from_utf8(digits).ok()?, 16).ok()
+    }
+}
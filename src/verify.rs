@@ -0,0 +1,20 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Checking that generated code is valid Rust.
+
+use error::{Error, Result};
+
+/// Parses `source` as a Rust file, without compiling it.
+pub fn verify(source: &[u8]) -> Result<()> {
+    let source = match ::std::str::from_utf8(source) {
+        Ok(x) => x,
+        Err(e) => return Err(Error::InvalidGeneratedCode(e.to_string())),
+    };
+
+    match ::syn::parse_file(source) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::InvalidGeneratedCode(e.to_string())),
+    }
+}
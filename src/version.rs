@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Parsing of the package version Cargo provides.
+
+/// The `major.minor.patch` numbers of a semver version like `1.2.3-beta+abc`,
+/// ignoring any pre-release and build metadata.
+pub fn parts(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut numbers = core.split('.').map(str::parse);
+
+    let major = numbers.next()?.ok()?;
+    let minor = numbers.next()?.ok()?;
+    let patch = numbers.next()?.ok()?;
+
+    if numbers.next().is_some() {
+        return None;
+    }
+
+    Some((major, minor, patch))
+}
+
+/// The build metadata of a semver version, after the `+`, like `abc` in
+/// `1.2.3-beta+abc`, or an empty string when there is none.
+pub fn build(version: &str) -> &str {
+    version.split_once('+').map_or("", |(_, build)| build)
+}
+
+/// The pre-release of a semver version, between the `-` and any `+`, like
+/// `beta` in `1.2.3-beta+abc`, or an empty string when there is none.
+pub fn pre(version: &str) -> &str {
+    let version = version.split('+').next().unwrap_or("");
+    version.split_once('-').map_or("", |(_, pre)| pre)
+}
+
+/// The version put back together from its parts, like `1.2.3-beta+abc`,
+/// leaving out pre-release and build metadata when they're empty.
+pub fn semver(version: &str) -> Option<String> {
+    let (major, minor, patch) = parts(version)?;
+    let mut semver = format!("{}.{}.{}", major, minor, patch);
+
+    if !pre(version).is_empty() {
+        semver.push('-');
+        semver.push_str(pre(version));
+    }
+
+    if !build(version).is_empty() {
+        semver.push('+');
+        semver.push_str(build(version));
+    }
+
+    Some(semver)
+}
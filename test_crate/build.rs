@@ -4,13 +4,39 @@
 
 extern crate build_details;
 
+use build_details::BuildDetail;
+
 fn main() {
     build_details::BuildDetails::all()
+        .serde_derive(true)
+        .lazy_build_info(true)
+        .version_gate(true)
         .generate("build_details.rs")
         .unwrap();
 
-    build_details::BuildDetails::require_all()
-        .exclude(build_details::BuildDetail::RustFlags)
-        .generate("required_build_details.rs")
+    build_details::BuildDetails::require_these(&[
+        BuildDetail::Timestamp,
+        BuildDetail::Version,
+        BuildDetail::Profile,
+        BuildDetail::Name,
+        BuildDetail::Authors,
+        BuildDetail::Description,
+        BuildDetail::Homepage,
+        BuildDetail::Cfg,
+        BuildDetail::Features,
+        BuildDetail::TargetCpu,
+        BuildDetail::PanicStrategy,
+        BuildDetail::FeaturesString,
+    ])
+    .generate("required_build_details.rs")
+    .unwrap();
+
+    build_details::BuildDetails::none()
+        .include(BuildDetail::Version)
+        .include(BuildDetail::Profile)
+        .include(BuildDetail::Timestamp)
+        .include(BuildDetail::CodegenUnits)
+        .optional_wrapper("::Maybe", "::Maybe::Just", "::Maybe::Nothing")
+        .generate("wrapped_build_details.rs")
         .unwrap();
 }
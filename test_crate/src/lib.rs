@@ -3,11 +3,27 @@
 // obtain one at https://mozilla.org/MPL/2.0/.
 
 extern crate phf;
+extern crate serde;
 
+#[allow(clippy::redundant_static_lifetimes)]
 pub mod build_details {
     include!(concat!(env!("OUT_DIR"), "/build_details.rs"));
 }
 
+#[allow(clippy::redundant_static_lifetimes)]
 pub mod required_build_details {
     include!(concat!(env!("OUT_DIR"), "/required_build_details.rs"));
 }
+
+/// Stands in for a crate's own optional type, for
+/// `BuildDetails::optional_wrapper`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Maybe<T> {
+    Just(T),
+    Nothing,
+}
+
+#[allow(clippy::redundant_static_lifetimes)]
+pub mod wrapped_build_details {
+    include!(concat!(env!("OUT_DIR"), "/wrapped_build_details.rs"));
+}
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate build_details;
+extern crate build_details_test;
+
+#[test]
+fn required() {
+    use build_details_test::required_build_details::*;
+
+    let map = build_details_map!(NAME, VERSION, FEATURES, TIMESTAMP);
+
+    let names: Vec<_> = map.iter().map(|x| x.0).collect();
+    assert_eq!(vec!["NAME", "VERSION", "FEATURES", "TIMESTAMP"], names);
+
+    assert_eq!(("NAME", "build_details_test".to_owned()), map[0]);
+    assert_eq!(("VERSION", "0.1.0".to_owned()), map[1]);
+    assert!(map[2].1.split(',').any(|x| x == "ON_BY_DEFAULT"));
+    assert!(map[3].1.parse::<u64>().unwrap() > 0);
+}
+
+#[test]
+fn optional() {
+    use build_details_test::build_details::*;
+
+    let map = build_details_map!(VERSION, FEATURES_STRING,);
+
+    assert_eq!(
+        vec![
+            ("VERSION", "0.1.0".to_owned()),
+            ("FEATURES_STRING", "DEFAULT,ON_BY_DEFAULT".to_owned()),
+        ],
+        map
+    );
+}
+
+#[test]
+fn missing() {
+    const ABSENT: Option<&'static str> = None;
+
+    assert!(build_details_map!(ABSENT).is_empty());
+}
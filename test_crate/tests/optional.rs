@@ -3,6 +3,7 @@
 // obtain one at https://mozilla.org/MPL/2.0/.
 
 extern crate build_details_test;
+extern crate serde_json;
 
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -31,6 +32,12 @@ fn rust_flags() {
     let _flags: Option<&'static str> = RUST_FLAGS;
 }
 
+#[test]
+fn codegen_units() {
+    // Only available when codegen-units is set through RUSTFLAGS.
+    let _units: Option<u32> = CODEGEN_UNITS;
+}
+
 #[test]
 fn homepage() {
     let expected = "http://example.com/?a_weird_character=\"\"";
@@ -50,6 +57,15 @@ fn version() {
     assert_eq!(Some("0.1.0"), VERSION);
 }
 
+#[test]
+fn is_at_least_version() {
+    const _: () = assert!(is_at_least(0, 1, 0));
+
+    assert!(is_at_least(0, 0, 9));
+    assert!(!is_at_least(0, 1, 1));
+    assert!(!is_at_least(1, 0, 0));
+}
+
 #[test]
 fn name() {
     assert_eq!(Some("build_details_test"), NAME);
@@ -99,3 +115,41 @@ fn features_on() {
 fn features_off() {
     assert!(!FEATURES.unwrap().contains(&"OFF_BY_DEFAULT"));
 }
+
+#[test]
+fn features_string() {
+    assert_eq!(Some("DEFAULT,ON_BY_DEFAULT"), FEATURES_STRING);
+}
+
+#[test]
+fn build_info_serialize() {
+    let json = serde_json::to_value(BUILD_INFO).unwrap();
+
+    assert_eq!(json["version"], "0.1.0");
+    assert_eq!(json["name"], "build_details_test");
+    assert!(json.get("cfg").is_none());
+}
+
+#[test]
+fn build_info_derives() {
+    let copy = BUILD_INFO;
+
+    assert_eq!(*build_info(), copy);
+    assert!(format!("{:?}", copy).starts_with("BuildInfo {"));
+}
+
+#[test]
+fn build_info_lazy() {
+    let info = build_info();
+
+    assert_eq!(VERSION, info.version);
+    assert!(::std::ptr::eq(info, build_info()));
+}
+
+#[test]
+fn build_info_numeric_fields() {
+    let info = build_info();
+
+    assert_eq!(TIMESTAMP, info.timestamp);
+    assert_eq!(CODEGEN_UNITS, BUILD_INFO.codegen_units);
+}
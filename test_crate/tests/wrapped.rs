@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+extern crate build_details_test;
+
+use build_details_test::wrapped_build_details::*;
+use build_details_test::Maybe;
+
+#[test]
+fn version() {
+    assert_eq!(Maybe::Just("0.1.0"), VERSION);
+}
+
+#[test]
+fn profile() {
+    match PROFILE {
+        Maybe::Just("debug") | Maybe::Just("release") => (),
+        _ => panic!("expected profile to be 'debug' or 'release'"),
+    }
+}
+
+#[test]
+fn timestamp() {
+    match TIMESTAMP {
+        Maybe::Just(x) => assert!(x > 0),
+        Maybe::Nothing => panic!("expected a timestamp"),
+    }
+}
+
+#[test]
+fn codegen_units() {
+    // Only available when codegen-units is set through RUSTFLAGS.
+    let _units: Maybe<u32> = CODEGEN_UNITS;
+}
@@ -0,0 +1,136 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "config")]
+
+extern crate build_details;
+extern crate serde_json;
+extern crate tempfile;
+
+use build_details::error::Error;
+use build_details::{BuildDetails, Config};
+
+use std::fs;
+use std::io::prelude::*;
+use std::io::SeekFrom;
+
+use tempfile::{tempdir, tempfile};
+
+#[test]
+fn from_config() {
+    let mut file = tempfile().unwrap();
+
+    let config: Config = serde_json::from_str(
+        r#"{
+            "include": ["Homepage"],
+            "require": ["Version"],
+            "placeholder_missing": "unknown"
+        }"#,
+    )
+    .unwrap();
+
+    BuildDetails::from_config(config)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.contains("pub const VERSION: &'static str = env!(\"CARGO_PKG_VERSION\");\n"));
+    assert!(actual.contains("None => \"unknown\""));
+}
+
+#[test]
+fn from_config_unknown_field() {
+    let result = serde_json::from_str::<Config>(r#"{ "includes": ["Version"] }"#);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn from_config_every_field() {
+    let out_dir = tempdir().unwrap();
+
+    let config: Config = serde_json::from_str(
+        r#"{
+            "include": ["Homepage"],
+            "require": ["Version", "Timestamp", "TargetCpu", "PanicStrategy"],
+            "serde_derive": true,
+            "lazy_build_info": true,
+            "placeholder_missing": "unknown",
+            "detect_duplicates": true,
+            "timestamp_signed": true,
+            "target_cpu_fallback": "generic",
+            "panic_strategy_fallback": "unwind",
+            "prefix": "BUILD_",
+            "rename": { "Version": "PKG_VERSION" },
+            "profile_specific": {
+                "debug": ["Description"],
+                "release": ["Name"]
+            }
+        }"#,
+    )
+    .unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+    ::std::env::set_var("PROFILE", "debug");
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+
+    let details = BuildDetails::from_config(config);
+
+    details.generate("build_details.rs").unwrap();
+
+    match details.generate("build_details.rs") {
+        Err(Error::AlreadyGenerated(_)) => (),
+        other => panic!("expected a duplicate, got {:?}", other),
+    }
+
+    ::std::env::remove_var("PROFILE");
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    let actual = fs::read_to_string(out_dir.path().join("build_details.rs")).unwrap();
+
+    for expected in &[
+        "pub const BUILD_PKG_VERSION: &'static str = env!(\"CARGO_PKG_VERSION\");\n",
+        "pub const BUILD_TIMESTAMP: i64 = 1500000000i64;\n",
+        "pub const BUILD_TARGET_CPU: &'static str = \"generic\";\n",
+        "pub const BUILD_PANIC_STRATEGY: &'static str = \"unwind\";\n",
+        "pub const BUILD_DESCRIPTION: Option<&'static str> = ",
+        "None => \"unknown\"",
+        "Serialize",
+        "pub fn build_info() -> &'static BuildInfo {\n",
+    ] {
+        assert!(
+            actual.contains(expected),
+            "{:?} not in {}",
+            expected,
+            actual
+        );
+    }
+
+    assert!(!actual.contains("NAME"));
+}
+
+#[test]
+fn from_config_default_fallbacks() {
+    let mut file = tempfile().unwrap();
+
+    let config: Config =
+        serde_json::from_str(r#"{ "require": ["TargetCpu", "PanicStrategy"] }"#).unwrap();
+
+    BuildDetails::from_config(config)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.contains("pub const TARGET_CPU: &'static str = \"default\";\n"));
+    assert!(actual.contains("pub const PANIC_STRATEGY: &'static str = \"unwind\";\n"));
+}
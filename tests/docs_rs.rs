@@ -0,0 +1,78 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+// Setting `DOCS_RS` changes what every configuration generates, so these tests
+// run in their own process.
+
+extern crate build_details;
+#[macro_use]
+extern crate lazy_static;
+extern crate tempfile;
+
+use build_details::{BuildDetail, BuildDetails};
+
+use std::io::prelude::*;
+use std::io::SeekFrom;
+use std::sync::Mutex;
+
+use tempfile::tempfile;
+
+lazy_static! {
+    static ref DOCS_RS: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn docs_rs_detected() {
+    let mut file = tempfile().unwrap();
+
+    let lock = DOCS_RS.lock().unwrap();
+
+    ::std::env::set_var("DOCS_RS", "1");
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "not a number");
+    ::std::env::set_var("PROFILE", "debug");
+
+    BuildDetails::none()
+        .require(BuildDetail::GitCommitShort)
+        .require(BuildDetail::Timestamp)
+        .include(BuildDetail::GitUpstream)
+        .order(&[
+            BuildDetail::GitCommitShort,
+            BuildDetail::Timestamp,
+            BuildDetail::GitUpstream,
+        ])
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .docs_rs_safe(false)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("DOCS_RS");
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+    ::std::env::remove_var("PROFILE");
+
+    BuildDetails::none()
+        .include(BuildDetail::GitUpstream)
+        .docs_rs_safe(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::mem::drop(lock);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const GIT_COMMIT_SHORT: &'static str = \"\";
+pub const TIMESTAMP: u64 = 0u64;
+pub const GIT_UPSTREAM: Option<&'static str> = None;
+pub const PROFILE: &'static str = \"debug\";
+pub const GIT_UPSTREAM: Option<&'static str> = None;\n",
+        &actual
+    );
+}
@@ -8,13 +8,16 @@ extern crate lazy_static;
 extern crate tempfile;
 
 use build_details::error::Error;
-use build_details::{BuildDetail, BuildDetails};
+use build_details::{formatters, BuildDetail, BuildDetails, ListFormat};
 
+use std::fs;
 use std::io::prelude::*;
 use std::io::SeekFrom;
+use std::path::Path;
+use std::process::Command;
 use std::sync::Mutex;
 
-use tempfile::tempfile;
+use tempfile::{tempdir, tempfile};
 
 #[test]
 fn version_required() {
@@ -56,10 +59,16 @@ fn version_optional() {
     );
 }
 
+lazy_static! {
+    static ref SOURCE_DATE_EPOCH: Mutex<()> = Mutex::new(());
+}
+
 #[test]
 fn timestamp_required() {
     let mut file = tempfile().unwrap();
 
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
     BuildDetails::none()
         .require(BuildDetail::Timestamp)
         .write_to(&mut file)
@@ -72,12 +81,16 @@ fn timestamp_required() {
 
     assert!(actual.starts_with("pub const TIMESTAMP: u64 ="));
     assert!(actual.ends_with(";\n"));
+
+    ::std::mem::drop(lock);
 }
 
 #[test]
 fn timestamp_optional() {
     let mut file = tempfile().unwrap();
 
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
     BuildDetails::none()
         .include(BuildDetail::Timestamp)
         .write_to(&mut file)
@@ -90,12 +103,292 @@ fn timestamp_optional() {
 
     assert!(actual.starts_with("pub const TIMESTAMP: Option<u64> = Some("));
     assert!(actual.ends_with(");\n"));
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn timestamp_signed() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Timestamp)
+        .timestamp_signed(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.starts_with("pub const TIMESTAMP: i64 ="));
+    assert!(actual.ends_with(";\n"));
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn timestamp_source_date_epoch() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    BuildDetails::none()
+        .require(BuildDetail::Timestamp)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!("pub const TIMESTAMP: u64 = 1500000000u64;\n", &actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn source_date_epoch_rerun() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    BuildDetails::none()
+        .require(BuildDetail::RerunInputs)
+        .include(BuildDetail::Timestamp)
+        .include(BuildDetail::BuildDateLocal)
+        .write_to(&mut file)
+        .unwrap();
+
+    let mut errors = vec![];
+
+    for epoch in &["abc", "-1"] {
+        ::std::env::set_var("SOURCE_DATE_EPOCH", epoch);
+
+        for detail in &[
+            BuildDetail::Timestamp,
+            BuildDetail::BuildDateLocal,
+            BuildDetail::BuildTzOffset,
+        ] {
+            let result = BuildDetails::none()
+                .include(*detail)
+                .write_to(&mut tempfile().unwrap());
+
+            match result {
+                Err(Error::InvalidSourceDateEpoch(x)) => errors.push(x),
+                other => panic!("expected an invalid epoch, got {:?}", other),
+            }
+        }
+    }
+
+    let banner = BuildDetails::none()
+        .output_banner(true)
+        .write_c_header(&mut tempfile().unwrap());
+    let clap = BuildDetails::none()
+        .clap_version_string("VERSION_STRING")
+        .write_to(&mut tempfile().unwrap());
+
+    for result in [banner, clap] {
+        match result {
+            Err(Error::InvalidSourceDateEpoch(x)) => errors.push(x),
+            other => panic!("expected an invalid epoch, got {:?}", other),
+        }
+    }
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    let mut unset = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::RerunInputs)
+        .include(BuildDetail::Timestamp)
+        .write_to(&mut unset)
+        .unwrap();
+
+    ::std::mem::drop(lock);
+
+    assert_eq!(
+        vec!["abc", "abc", "abc", "-1", "-1", "-1", "-1", "-1"],
+        errors
+    );
+
+    unset.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    unset.read_to_string(&mut actual).unwrap();
+
+    assert!(!actual.contains("SOURCE_DATE_EPOCH"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.contains(
+        "pub const RERUN_INPUTS: &\'static [&\'static str] = &[
+    \"rerun-if-env-changed=SOURCE_DATE_EPOCH\",
+];"
+    ));
+}
+
+#[test]
+fn build_tz_offset() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    let tz = ::std::env::var("TZ").ok();
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    for zone in &["UTC0", "JST-9", "<-0330>3:30"] {
+        ::std::env::set_var("TZ", zone);
+
+        BuildDetails::none()
+            .require(BuildDetail::BuildTzOffset)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    match tz {
+        Some(tz) => ::std::env::set_var("TZ", tz),
+        None => ::std::env::remove_var("TZ"),
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BUILD_TZ_OFFSET: i32 = 0i32;
+pub const BUILD_TZ_OFFSET: i32 = 32400i32;
+pub const BUILD_TZ_OFFSET: i32 = -12600i32;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn build_date_local() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    let tz = ::std::env::var("TZ").ok();
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    for zone in &["UTC0", "JST-9", "<-0330>3:30"] {
+        ::std::env::set_var("TZ", zone);
+
+        BuildDetails::none()
+            .require(BuildDetail::BuildDateLocal)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    match tz {
+        Some(tz) => ::std::env::set_var("TZ", tz),
+        None => ::std::env::remove_var("TZ"),
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BUILD_DATE_LOCAL: &\'static str = \"2017-07-14T02:40:00+00:00\";
+pub const BUILD_DATE_LOCAL: &\'static str = \"2017-07-14T11:40:00+09:00\";
+pub const BUILD_DATE_LOCAL: &\'static str = \"2017-07-13T23:10:00-03:30\";\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn reproducibility_report() {
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    let report = BuildDetails::all()
+        .include(BuildDetail::BuildCwd)
+        .reproducibility_report();
+
+    let flagged: Vec<_> = report.issues().iter().map(|x| x.detail()).collect();
+    assert_eq!(vec![BuildDetail::BuildCwd, BuildDetail::Timestamp], flagged);
+    assert!(!report.is_reproducible());
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    assert!(BuildDetails::all()
+        .reproducibility_report()
+        .is_reproducible());
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    ::std::mem::drop(lock);
 }
 
 lazy_static! {
     static ref PROFILE: Mutex<()> = Mutex::new(());
 }
 
+lazy_static! {
+    static ref DEBUG: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn debug_level() {
+    let mut file = tempfile().unwrap();
+
+    let lock = DEBUG.lock().unwrap();
+
+    for level in &["1", "true", "false", "full"] {
+        ::std::env::set_var("DEBUG", level);
+
+        BuildDetails::none()
+            .include(BuildDetail::DebugLevel)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::remove_var("DEBUG");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const DEBUG_LEVEL: Option<u8> = Some(1u8);
+pub const DEBUG_LEVEL: Option<u8> = Some(2u8);
+pub const DEBUG_LEVEL: Option<u8> = Some(0u8);
+pub const DEBUG_LEVEL: Option<u8> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
 #[test]
 fn profile_required_missing() {
     let mut file = tempfile().unwrap();
@@ -117,6 +410,36 @@ fn profile_required_missing() {
     ::std::mem::drop(lock);
 }
 
+#[test]
+fn profile_required_deferred() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::remove_var("PROFILE");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .defer_missing(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "#[derive(Clone, Copy)]
+pub struct MissingBuildDetail;
+#[deprecated(note = \"build detail PROFILE was missing while generating\")]
+pub const PROFILE: MissingBuildDetail = MissingBuildDetail;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
 #[test]
 fn profile_required_available() {
     let mut file = tempfile().unwrap();
@@ -191,3 +514,3705 @@ fn profile_optional_missing() {
 
     ::std::mem::drop(lock);
 }
+
+lazy_static! {
+    static ref RUSTFLAGS: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn codegen_units_optional_available() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var(
+        "CARGO_ENCODED_RUSTFLAGS",
+        "-Ccodegen-units=4\x1f-C\x1fcodegen-units=16",
+    );
+
+    BuildDetails::none()
+        .include(BuildDetail::CodegenUnits)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const CODEGEN_UNITS: Option<u32> = Some(16u32);\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn codegen_units_required_missing() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::set_var("RUSTFLAGS", "-C opt-level=3");
+
+    let result = BuildDetails::none()
+        .require(BuildDetail::CodegenUnits)
+        .write_to(&mut file)
+        .unwrap_err();
+
+    match result {
+        Error::MissingDetail(ref x) if x == "CODEGEN_UNITS" => (),
+        _ => panic!("Expected Error::MissingDetail(CODEGEN_UNITS)"),
+    }
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn serde_derive() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .serde_derive(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = "pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(::serde::Serialize)]
+pub struct BuildInfo {
+    pub version: &\'static str,
+}
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: VERSION,
+};
+";
+
+    assert_eq!(expected, &actual);
+}
+
+#[test]
+fn struct_derives() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .include(BuildDetail::Cfg)
+        .struct_derives(&["Debug", "Default"])
+        .lazy_build_info(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let start = actual.find("#[derive").unwrap();
+
+    let expected = "#[derive(Debug, Default)]
+pub struct BuildInfo {
+    pub version: &\'static str,
+}
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: VERSION,
+};
+";
+
+    assert!(actual[start..].starts_with(expected));
+}
+
+#[test]
+fn placeholder_missing() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::remove_var("PROFILE");
+
+    BuildDetails::none()
+        .include(BuildDetail::Profile)
+        .placeholder_missing("unknown")
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const PROFILE: Option<&\'static str> = Some(\"unknown\");\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn none_as() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::remove_var("PROFILE");
+
+    BuildDetails::none()
+        .include(BuildDetail::Profile)
+        .none_as("crate::UNKNOWN")
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::none()
+        .include(BuildDetail::Profile)
+        .none_as("crate::UNKNOWN")
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const PROFILE: Option<&\'static str> = crate::UNKNOWN;
+pub const PROFILE: Option<&\'static str> = Some(\"release\");\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn placeholder_missing_env() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::Homepage)
+        .placeholder_missing("unknown")
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const HOMEPAGE: Option<&\'static str> = \
+         Some(match option_env!(\"CARGO_PKG_HOMEPAGE\") { Some(x) => x, None => \"unknown\" });\n",
+        &actual
+    );
+}
+
+lazy_static! {
+    static ref MANIFEST_DIR: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn workspace_root_member() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    let member = root.path().join("member");
+    fs::create_dir(&member).unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[workspace]\nmembers = [\"member\"]\n",
+    )
+    .unwrap();
+    fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", &member);
+
+    BuildDetails::none()
+        .require(BuildDetail::WorkspaceRoot)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = format!(
+        "pub const WORKSPACE_ROOT: &\'static str = {:?};\n",
+        root.path().to_str().unwrap()
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn resolver_version() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    let member = root.path().join("member");
+    fs::create_dir(&member).unwrap();
+    fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", &member);
+
+    let manifests = [
+        "[workspace]\nmembers = [\"member\"]\nresolver = \"2\" # features\n",
+        "[package]\nname = \"root\"\nedition = \"2021\"\n[workspace]\n",
+        "[package]\nname = \"root\"\nedition = \"2018\"\n[workspace]\n",
+        "[workspace]\nmembers = [\"member\"]\n",
+    ];
+
+    for manifest in &manifests {
+        fs::write(root.path().join("Cargo.toml"), manifest).unwrap();
+
+        BuildDetails::none()
+            .require(BuildDetail::ResolverVersion)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const RESOLVER_VERSION: &\'static str = \"2\";
+pub const RESOLVER_VERSION: &\'static str = \"2\";
+pub const RESOLVER_VERSION: &\'static str = \"1\";
+pub const RESOLVER_VERSION: &\'static str = \"1\";\n",
+        actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn profile_overrides() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    fs::create_dir(root.path().join(".cargo")).unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[package]\nname = \"root\"\n\n\
+         [profile.release]\n\
+         opt-level = \"s\"\n\
+         lto = true # smaller\n\
+         codegen-units = 16\n\n\
+         [profile.dev]\n\
+         opt-level = 1\n",
+    )
+    .unwrap();
+    fs::write(
+        root.path().join(".cargo/config.toml"),
+        "[profile.release]\ncodegen-units = 1\n",
+    )
+    .unwrap();
+
+    let manifest_lock = MANIFEST_DIR.lock().unwrap();
+    let profile_lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+    ::std::env::set_var("PROFILE", "release");
+    ::std::env::set_var("CARGO_PROFILE_RELEASE_LTO", "fat");
+
+    BuildDetails::none()
+        .require(BuildDetail::ProfileOverrides)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_PROFILE_RELEASE_LTO");
+    ::std::env::set_var("PROFILE", "debug");
+
+    BuildDetails::none()
+        .include(BuildDetail::ProfileOverrides)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("PROFILE");
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let declaration = "#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileOverrides {
+    pub opt_level: Option<&\'static str>,
+    pub lto: Option<&\'static str>,
+    pub codegen_units: Option<u32>,
+    pub debug: Option<&\'static str>,
+}
+";
+
+    let expected = format!(
+        "{}pub const PROFILE_OVERRIDES: ProfileOverrides = ProfileOverrides {{ \
+         opt_level: Some(\"s\"), lto: Some(\"fat\"), codegen_units: Some(1u32), debug: None }};
+{}pub const PROFILE_OVERRIDES: Option<ProfileOverrides> = Some(ProfileOverrides {{ \
+         opt_level: Some(\"1\"), lto: None, codegen_units: None, debug: None }});\n",
+        declaration, declaration
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(profile_lock);
+    ::std::mem::drop(manifest_lock);
+}
+
+#[test]
+fn profile_overrides_impl_on() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[package]\nname = \"root\"\n\n[profile.dev]\nopt-level = 1\n",
+    )
+    .unwrap();
+
+    let manifest_lock = MANIFEST_DIR.lock().unwrap();
+    let profile_lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+    ::std::env::set_var("PROFILE", "debug");
+
+    BuildDetails::none()
+        .require(BuildDetail::ProfileOverrides)
+        .impl_on("Build")
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::ProfileOverrides)
+        .namespaced(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("PROFILE");
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let declaration = "#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileOverrides {
+    pub opt_level: Option<&\'static str>,
+    pub lto: Option<&\'static str>,
+    pub codegen_units: Option<u32>,
+    pub debug: Option<&\'static str>,
+}
+";
+
+    let value = "ProfileOverrides { \
+                 opt_level: Some(\"1\"), lto: None, codegen_units: None, debug: None }";
+
+    let expected = format!(
+        "{}pub struct Build;
+impl Build {{
+    pub const PROFILE_OVERRIDES: ProfileOverrides = {};
+}}
+{}pub mod toolchain {{
+    use super::ProfileOverrides;
+    pub const PROFILE_OVERRIDES: ProfileOverrides = {};
+}}\n",
+        declaration, value, declaration, value
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(profile_lock);
+    ::std::mem::drop(manifest_lock);
+}
+
+#[test]
+fn workspace_member_count() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+
+    for member in &[
+        "crates/a",
+        "crates/b",
+        "crates/old",
+        "crates/notes",
+        "tools/cli",
+    ] {
+        let dir = root.path().join(member);
+        fs::create_dir_all(&dir).unwrap();
+
+        if !member.ends_with("notes") {
+            fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+        }
+    }
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path().join("tools/cli"));
+
+    let manifests = [
+        "[package]\nname = \"root\"\n\n\
+         [workspace]\n\
+         members = [\n    \"crates/*\",\n    \"./tools/cli\",\n]\n\
+         exclude = [\"crates/old\"]\n",
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    ];
+
+    for manifest in &manifests {
+        fs::write(root.path().join("Cargo.toml"), manifest).unwrap();
+
+        BuildDetails::none()
+            .require(BuildDetail::WorkspaceMemberCount)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[package]\nname = \"root\"\n",
+    )
+    .unwrap();
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::WorkspaceMemberCount)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const WORKSPACE_MEMBER_COUNT: u32 = 4u32;
+pub const WORKSPACE_MEMBER_COUNT: u32 = 3u32;
+pub const WORKSPACE_MEMBER_COUNT: u32 = 1u32;\n",
+        actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn lockfile_hash() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[package]\nname = \"root\"\n",
+    )
+    .unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+
+    BuildDetails::none()
+        .include(BuildDetail::LockfileHash)
+        .write_to(&mut file)
+        .unwrap();
+
+    fs::write(root.path().join("Cargo.lock"), "abc").unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::LockfileHash)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const LOCKFILE_HASH: Option<&\'static str> = None;
+pub const LOCKFILE_HASH: Option<&\'static str> = \
+         Some(\"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\");\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn vendor_dirty() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[package]\nname = \"app\"\n",
+    )
+    .unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+
+    let mut details = BuildDetails::none();
+    details.include(BuildDetail::VendorDirty);
+
+    // No vendor directory.
+    details.write_to(&mut file).unwrap();
+
+    let krate = root.path().join("third-party").join("dep");
+    fs::create_dir_all(krate.join("src")).unwrap();
+    fs::create_dir(root.path().join(".cargo")).unwrap();
+    fs::write(
+        root.path().join(".cargo").join("config.toml"),
+        "[source.crates-io]\nreplace-with = \"vendored-sources\"\n\n\
+         [source.vendored-sources]\ndirectory = \"third-party\"\n",
+    )
+    .unwrap();
+    fs::write(krate.join("src").join("lib.rs"), "hello").unwrap();
+    fs::write(krate.join("big.bin"), vec![b'x'; 1000]).unwrap();
+    fs::write(
+        krate.join(".cargo-checksum.json"),
+        "{\"files\":{\
+         \"src\\/lib.rs\":\"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\",\
+         \"big.bin\":\"44f8354494a5ba03ba1792a8d3e9c534c47a9181980fde7a3f44b06ef2ae7c7f\"},\
+         \"package\":null}",
+    )
+    .unwrap();
+
+    details.write_to(&mut file).unwrap();
+
+    fs::write(krate.join("src").join("lib.rs"), "hello!").unwrap();
+
+    details.write_to(&mut file).unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const VENDOR_DIRTY: Option<bool> = None;
+pub const VENDOR_DIRTY: Option<bool> = Some(false);
+pub const VENDOR_DIRTY: Option<bool> = Some(true);\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn source_loc() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    let src = root.path().join("src");
+    fs::create_dir_all(src.join("nested")).unwrap();
+    fs::write(src.join("lib.rs"), "mod nested;\n\nfn main() {}\n").unwrap();
+    fs::write(src.join("nested").join("mod.rs"), "// no newline").unwrap();
+    fs::write(src.join("notes.txt"), "not\ncounted\n").unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::SourceLoc)
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::SourceLoc)
+        .namespaced(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const SOURCE_LOC: u32 = 4u32;
+pub mod build {
+    pub const SOURCE_LOC: u32 = 4u32;
+}\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn workspace_root_single_crate() {
+    let mut file = tempfile().unwrap();
+
+    let root = tempdir().unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        "[package]\nname = \"single\"\n",
+    )
+    .unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", root.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::WorkspaceRoot)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = format!(
+        "pub const WORKSPACE_ROOT: &\'static str = {:?};\n",
+        root.path().to_str().unwrap()
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(lock);
+}
+
+lazy_static! {
+    static ref OUT_DIR: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn detect_duplicates() {
+    let out_dir = tempdir().unwrap();
+
+    let lock = OUT_DIR.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+
+    let mut details = BuildDetails::none();
+    details
+        .include(BuildDetail::Version)
+        .detect_duplicates(true);
+
+    details.generate("first.rs").unwrap();
+    details.generate("second.rs").unwrap();
+
+    let result = details.generate("first.rs").unwrap_err();
+
+    match result {
+        Error::AlreadyGenerated(ref x) if *x == out_dir.path().join("first.rs") => (),
+        _ => panic!("Expected Error::AlreadyGenerated(first.rs)"),
+    }
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn duplicates_allowed_by_default() {
+    let out_dir = tempdir().unwrap();
+
+    let lock = OUT_DIR.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+
+    let mut details = BuildDetails::none();
+    details.include(BuildDetail::Version);
+
+    details.generate("build_details.rs").unwrap();
+    details.generate("build_details.rs").unwrap();
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn out_dir() {
+    let out_dir = tempdir().unwrap();
+
+    let lock = OUT_DIR.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::OutDir)
+        .generate("build_details.rs")
+        .unwrap();
+
+    let actual = fs::read_to_string(out_dir.path().join("build_details.rs")).unwrap();
+
+    let expected = format!(
+        "pub const OUT_DIR: &\'static str = {:?};\n",
+        out_dir.path().to_str().unwrap()
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn output_dir() {
+    let out_dir = tempdir().unwrap();
+    let elsewhere = tempdir().unwrap();
+
+    let lock = OUT_DIR.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .output_dir(elsewhere.path().to_path_buf())
+        .generate("build_details.rs")
+        .unwrap();
+
+    assert!(elsewhere.path().join("build_details.rs").is_file());
+    assert!(!out_dir.path().join("build_details.rs").exists());
+
+    ::std::mem::drop(lock);
+}
+
+#[cfg(unix)]
+#[test]
+fn out_dir_not_writable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let out_dir = tempdir().unwrap();
+    fs::set_permissions(out_dir.path(), fs::Permissions::from_mode(0o555)).unwrap();
+
+    // Permissions don't apply to root.
+    let probe = out_dir.path().join("probe");
+    if fs::write(&probe, "").is_ok() {
+        fs::remove_file(&probe).unwrap();
+        return;
+    }
+
+    let result = BuildDetails::none()
+        .require(BuildDetail::Version)
+        .output_dir(out_dir.path().to_path_buf())
+        .generate("build_details.rs")
+        .unwrap_err();
+
+    match result {
+        Error::OutDirNotWritable(ref x) if x == out_dir.path() => (),
+        _ => panic!("Expected Error::OutDirNotWritable"),
+    }
+
+    fs::set_permissions(out_dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn with_source_path() {
+    let out_dir = tempdir().unwrap();
+
+    let lock = OUT_DIR.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .with_source_path(true)
+        .generate("version.rs")
+        .unwrap();
+
+    let actual = fs::read_to_string(out_dir.path().join("version.rs")).unwrap();
+
+    let expected = "pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");\n\
+                    pub const GENERATED_FROM: &\'static str = \"version.rs\";\n";
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn with_target_label() {
+    let out_dir = tempdir().unwrap();
+
+    let lock = OUT_DIR.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+
+    for label in &["x86_64-linux", "aarch64-macos"] {
+        BuildDetails::none()
+            .require(BuildDetail::Version)
+            .with_target_label(label)
+            .generate("version.rs")
+            .unwrap();
+    }
+
+    let actual = fs::read_to_string(out_dir.path().join("version.aarch64-macos.rs")).unwrap();
+
+    let expected = "pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");\n\
+                    pub const TARGET_LABEL: &\'static str = \"aarch64-macos\";\n";
+
+    assert_eq!(expected, actual);
+    assert!(out_dir.path().join("version.x86_64-linux.rs").exists());
+    assert!(!out_dir.path().join("version.rs").exists());
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn with_gen_timing() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .with_gen_timing(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let value = actual
+        .trim_end()
+        .trim_start_matches("pub const BUILD_DETAILS_GEN_MS: u64 = ")
+        .trim_end_matches("u64;");
+
+    assert!(value.parse::<u64>().is_ok(), "{}", actual);
+
+    let report = BuildDetails::none()
+        .with_gen_timing(true)
+        .reproducibility_report();
+
+    let flagged: Vec<_> = report.outputs().iter().map(|x| x.name()).collect();
+    assert_eq!(vec!["BUILD_DETAILS_GEN_MS"], flagged);
+    assert!(report.issues().is_empty());
+    assert!(!report.is_reproducible());
+    assert_eq!(
+        "BUILD_DETAILS_GEN_MS: time taken to generate the details",
+        report.to_string()
+    );
+
+    assert!(BuildDetails::none()
+        .reproducibility_report()
+        .is_reproducible());
+}
+
+#[test]
+fn warn_on_missing() {
+    let out_dir = tempdir().unwrap();
+
+    let out_lock = OUT_DIR.lock().unwrap();
+    let profile_lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("OUT_DIR", out_dir.path());
+    ::std::env::remove_var("PROFILE");
+
+    BuildDetails::none()
+        .include(BuildDetail::Profile)
+        .warn_on_missing(true)
+        .generate("build_details.rs")
+        .unwrap();
+
+    let actual = fs::read_to_string(out_dir.path().join("build_details.rs")).unwrap();
+
+    assert_eq!("pub const PROFILE: Option<&\'static str> = None;\n", actual);
+
+    ::std::mem::drop(profile_lock);
+    ::std::mem::drop(out_lock);
+}
+
+#[test]
+fn build_nonce_required() {
+    let mut details = BuildDetails::none();
+    details.require(BuildDetail::BuildNonce);
+
+    let mut first = vec![];
+    details.write_to(&mut first).unwrap();
+    let first = String::from_utf8(first).unwrap();
+
+    let mut second = vec![];
+    details.write_to(&mut second).unwrap();
+    let second = String::from_utf8(second).unwrap();
+
+    let prefix = "pub const BUILD_NONCE: &\'static str = \"";
+    let suffix = "\";\n";
+
+    assert!(first.starts_with(prefix) && first.ends_with(suffix));
+
+    let nonce = &first[prefix.len()..first.len() - suffix.len()];
+    assert_eq!(32, nonce.len());
+    assert!(nonce.chars().all(|x| x.is_ascii_hexdigit()));
+
+    assert_ne!(first, second);
+
+    let report = details.reproducibility_report();
+    let flagged: Vec<_> = report.issues().iter().map(|x| x.detail()).collect();
+    assert_eq!(vec![BuildDetail::BuildNonce], flagged);
+}
+
+lazy_static! {
+    static ref LOCALE: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn cargo_offline() {
+    let mut file = tempfile().unwrap();
+
+    for offline in &["true", "0"] {
+        ::std::env::set_var("CARGO_NET_OFFLINE", offline);
+
+        BuildDetails::none()
+            .require(BuildDetail::CargoOffline)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::remove_var("CARGO_NET_OFFLINE");
+
+    BuildDetails::none()
+        .require(BuildDetail::CargoOffline)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const CARGO_OFFLINE: bool = true;
+pub const CARGO_OFFLINE: bool = false;
+pub const CARGO_OFFLINE: bool = false;\n",
+        &actual
+    );
+}
+
+lazy_static! {
+    static ref SHELL: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn build_shell() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SHELL.lock().unwrap();
+
+    let shell = ::std::env::var_os("SHELL");
+
+    ::std::env::set_var("SHELL", "/bin/zsh");
+
+    BuildDetails::none()
+        .include(BuildDetail::BuildShell)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("SHELL");
+
+    BuildDetails::none()
+        .include(BuildDetail::BuildShell)
+        .write_to(&mut file)
+        .unwrap();
+
+    if let Some(shell) = shell {
+        ::std::env::set_var("SHELL", shell);
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BUILD_SHELL: Option<&\'static str> = Some(\"/bin/zsh\");
+pub const BUILD_SHELL: Option<&\'static str> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn build_locale() {
+    let mut file = tempfile().unwrap();
+
+    let lock = LOCALE.lock().unwrap();
+
+    let lang = ::std::env::var_os("LANG");
+    let lc_all = ::std::env::var_os("LC_ALL");
+
+    ::std::env::set_var("LANG", "en_CA.UTF-8");
+    ::std::env::set_var("LC_ALL", "fr_CA.UTF-8");
+
+    BuildDetails::none()
+        .include(BuildDetail::BuildLocale)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("LC_ALL", "");
+
+    BuildDetails::none()
+        .include(BuildDetail::BuildLocale)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("LANG");
+    ::std::env::remove_var("LC_ALL");
+
+    BuildDetails::none()
+        .include(BuildDetail::BuildLocale)
+        .write_to(&mut file)
+        .unwrap();
+
+    if let Some(lang) = lang {
+        ::std::env::set_var("LANG", lang);
+    }
+
+    if let Some(lc_all) = lc_all {
+        ::std::env::set_var("LC_ALL", lc_all);
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BUILD_LOCALE: Option<&\'static str> = Some(\"fr_CA.UTF-8\");
+pub const BUILD_LOCALE: Option<&\'static str> = Some(\"en_CA.UTF-8\");
+pub const BUILD_LOCALE: Option<&\'static str> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn build_cwd_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::BuildCwd)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let cwd = ::std::env::current_dir().unwrap();
+    let expected = format!(
+        "pub const BUILD_CWD: &\'static str = {:?};\n",
+        cwd.to_str().unwrap()
+    );
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn lazy_build_info() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .lazy_build_info(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = "pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub version: &\'static str,
+}
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: VERSION,
+};
+pub fn build_info() -> &\'static BuildInfo {
+    static CELL: ::std::sync::OnceLock<BuildInfo> = ::std::sync::OnceLock::new();
+    CELL.get_or_init(|| BUILD_INFO)
+}
+";
+
+    assert_eq!(expected, &actual);
+}
+
+#[test]
+fn newtypes() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .include(BuildDetail::FeaturesString)
+        .include(BuildDetail::CodegenUnits)
+        .order(&[
+            BuildDetail::Version,
+            BuildDetail::FeaturesString,
+            BuildDetail::CodegenUnits,
+        ])
+        .lazy_build_info(true)
+        .struct_derives(&["Debug"])
+        .newtypes(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let start = actual.find("#[derive").unwrap();
+    let end = actual.find("pub fn build_info").unwrap();
+
+    let expected = "#[derive(Debug)]
+pub struct Version(pub &\'static str);
+#[derive(Debug)]
+pub struct FeaturesString(pub &\'static str);
+#[derive(Debug)]
+pub struct BuildInfo {
+    pub version: Version,
+    pub features_string: Option<FeaturesString>,
+    pub codegen_units: Option<u32>,
+}
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: Version(VERSION),
+    features_string: match FEATURES_STRING { Some(x) => Some(FeaturesString(x)), None => None },
+    codegen_units: CODEGEN_UNITS,
+};
+";
+
+    assert_eq!(expected, &actual[start..end]);
+}
+
+#[test]
+fn use_static() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .serde_derive(true)
+        .struct_derives(&[])
+        .use_static(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = "pub static VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+#[derive(::serde::Serialize)]
+pub struct BuildInfo {
+    pub version: &\'static str,
+}
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: VERSION,
+};
+";
+
+    assert_eq!(expected, &actual);
+}
+
+#[test]
+fn as_cow() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .include(BuildDetail::Homepage)
+        .include(BuildDetail::CodegenUnits)
+        .order(&[
+            BuildDetail::Profile,
+            BuildDetail::Homepage,
+            BuildDetail::CodegenUnits,
+        ])
+        .serde_derive(true)
+        .struct_derives(&["Clone"])
+        .as_cow(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = "pub const PROFILE: ::std::borrow::Cow<\'static, str> = \
+                    ::std::borrow::Cow::Borrowed(\"release\");
+pub const HOMEPAGE: Option<::std::borrow::Cow<\'static, str>> = \
+                    match option_env!(\"CARGO_PKG_HOMEPAGE\") { \
+                    Some(x) => Some(::std::borrow::Cow::Borrowed(x)), None => None };
+pub const CODEGEN_UNITS: Option<u32> = None;
+#[derive(Clone)]
+#[derive(::serde::Serialize)]
+pub struct BuildInfo {
+    pub profile: ::std::borrow::Cow<\'static, str>,
+    pub homepage: Option<::std::borrow::Cow<\'static, str>>,
+    pub codegen_units: Option<u32>,
+}
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    profile: PROFILE,
+    homepage: HOMEPAGE,
+    codegen_units: CODEGEN_UNITS,
+};
+";
+
+    assert_eq!(expected, &actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn with_value_checksums() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "123456789");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .include(BuildDetail::Homepage)
+        .include(BuildDetail::CodegenUnits)
+        .order(&[
+            BuildDetail::Profile,
+            BuildDetail::Homepage,
+            BuildDetail::CodegenUnits,
+        ])
+        .with_value_checksums(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const PROFILE: &\'static str = \"123456789\";
+pub const PROFILE_CRC: u32 = 3421780262u32;
+pub const HOMEPAGE: Option<&\'static str> = option_env!(\"CARGO_PKG_HOMEPAGE\");
+pub const HOMEPAGE_CRC: Option<u32> = Some(0u32);
+pub const CODEGEN_UNITS: Option<u32> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+lazy_static! {
+    static ref TARGET: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn linker_from_rustflags() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::set_var("RUSTFLAGS", "-C linker=clang -C opt-level=3");
+
+    BuildDetails::none()
+        .require(BuildDetail::Linker)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!("pub const LINKER: &\'static str = \"clang\";\n", &actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn linker_from_target_env() {
+    let mut file = tempfile().unwrap();
+
+    let rustflags = RUSTFLAGS.lock().unwrap();
+    let target = TARGET.lock().unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("RUSTFLAGS");
+    ::std::env::set_var("TARGET", "x86_64-unknown-linux-gnu");
+    ::std::env::set_var("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_LINKER", "mold");
+
+    BuildDetails::none()
+        .include(BuildDetail::Linker)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_LINKER");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const LINKER: Option<&\'static str> = Some(\"mold\");\n",
+        &actual
+    );
+
+    ::std::mem::drop(target);
+    ::std::mem::drop(rustflags);
+}
+
+#[test]
+fn git_author_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::GitAuthor)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%an <%ae>"])
+        .output()
+        .unwrap();
+    let author = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "pub const GIT_AUTHOR: &\'static str = {:?};\n",
+        author.trim_end()
+    );
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn clap_version_string() {
+    let mut file = tempfile().unwrap();
+
+    let epoch_lock = SOURCE_DATE_EPOCH.lock().unwrap();
+    let version_lock = VERSION.lock().unwrap();
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    BuildDetails::none()
+        .clap_version_string("VERSION_STRING")
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    ::std::mem::drop(version_lock);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "pub const VERSION_STRING: &\'static str = \"{} ({} 2017-07-14)\";\n",
+        env!("CARGO_PKG_VERSION"),
+        commit.trim_end()
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(epoch_lock);
+}
+
+#[test]
+fn git_commit_subject_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::GitCommitSubject)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%s"])
+        .output()
+        .unwrap();
+    let subject = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "pub const GIT_COMMIT_SUBJECT: &\'static str = {:?};\n",
+        subject.trim_end()
+    );
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn assert_git_in_release() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::none()
+        .assert_git_in_release(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("PROFILE", "debug");
+
+    BuildDetails::none()
+        .assert_git_in_release(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::mem::drop(lock);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "pub const GIT_COMMIT_SHORT: &\'static str = {:?};
+#[allow(dead_code)]
+fn __assert_git_in_release() -> &\'static str {{
+    GIT_COMMIT_SHORT
+}}
+",
+        commit.trim_end()
+    );
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn git_commit_short_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::GitCommitShort)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "pub const GIT_COMMIT_SHORT: &\'static str = {:?};\n",
+        commit.trim_end()
+    );
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn git_ref_optional() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::GitRef)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["symbolic-ref", "-q", "HEAD"])
+        .output()
+        .unwrap();
+
+    let expected = if output.status.success() {
+        let head = String::from_utf8(output.stdout).unwrap();
+        format!(
+            "pub const GIT_REF: Option<&\'static str> = Some({:?});\n",
+            head.trim_end()
+        )
+    } else {
+        "pub const GIT_REF: Option<&\'static str> = None;\n".to_owned()
+    };
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn git_upstream_optional() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::GitUpstream)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .output()
+        .unwrap();
+
+    let expected = if output.status.success() {
+        let upstream = String::from_utf8(output.stdout).unwrap();
+        format!(
+            "pub const GIT_UPSTREAM: Option<&\'static str> = Some({:?});\n",
+            upstream.trim_end()
+        )
+    } else {
+        "pub const GIT_UPSTREAM: Option<&\'static str> = None;\n".to_owned()
+    };
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn smart_version() {
+    let mut file = tempfile().unwrap();
+
+    let lock = VERSION.lock().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::SmartVersion)
+        .smart_version_template("{version}+{git_short}{dirty}")
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::mem::drop(lock);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let git = |args: &[&str]| Command::new("git").args(args).output().unwrap();
+
+    let short = git(&["rev-parse", "--short", "HEAD"]);
+    let tagged = git(&["describe", "--tags", "--exact-match", "HEAD"]);
+    let status = git(&["status", "--porcelain", "--untracked-files=no"]);
+    let dirty = !status.stdout.is_empty();
+
+    let version = if !short.status.success() || (tagged.status.success() && !dirty) {
+        env!("CARGO_PKG_VERSION").to_owned()
+    } else {
+        format!(
+            "{}+{}{}",
+            env!("CARGO_PKG_VERSION"),
+            String::from_utf8(short.stdout).unwrap().trim_end(),
+            if dirty { "-dirty" } else { "" }
+        )
+    };
+
+    let expected = format!("pub const SMART_VERSION: &\'static str = {:?};\n", version);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn order() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::Description)
+        .include(BuildDetail::Authors)
+        .require(BuildDetail::Version)
+        .require(BuildDetail::Name)
+        .order(&[
+            BuildDetail::Name,
+            BuildDetail::Homepage,
+            BuildDetail::Description,
+            BuildDetail::Version,
+        ])
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let names: Vec<_> = actual
+        .lines()
+        .map(|x| x.split(':').next().unwrap())
+        .collect();
+
+    assert_eq!(
+        vec![
+            "pub const NAME",
+            "pub const DESCRIPTION",
+            "pub const VERSION",
+            "pub const AUTHORS",
+        ],
+        names
+    );
+}
+
+#[test]
+fn minimal() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::minimal().write_to(&mut file).unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let mut names: Vec<_> = actual
+        .lines()
+        .map(|x| x.split(':').next().unwrap())
+        .collect();
+    names.sort();
+
+    assert_eq!(
+        vec!["pub const GIT_COMMIT_SHORT", "pub const VERSION"],
+        names
+    );
+}
+
+#[test]
+fn git_worktree_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::GitWorktree)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .unwrap();
+    let toplevel = String::from_utf8(output.stdout).unwrap();
+
+    let expected = format!(
+        "pub const GIT_WORKTREE: &\'static str = {:?};\n",
+        toplevel.trim_end()
+    );
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn git_stash_count_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::GitStashCount)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("git")
+        .args(["stash", "list"])
+        .output()
+        .unwrap();
+    let count = String::from_utf8(output.stdout).unwrap().lines().count();
+
+    let expected = format!("pub const GIT_STASH_COUNT: u32 = {}u32;\n", count);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn git_stash_count_rerun() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::RerunInputs)
+        .require(BuildDetail::GitStashCount)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let watched: Vec<_> = actual
+        .lines()
+        .filter_map(|x| x.trim().strip_prefix("\"rerun-if-changed="))
+        .map(|x| Path::new(x.trim_end_matches("\",")).to_owned())
+        .collect();
+
+    assert!(!watched.is_empty(), "{}", actual);
+
+    for path in watched {
+        assert!(path.is_absolute() && path.exists(), "{}", path.display());
+    }
+}
+
+#[test]
+fn target_cpu_available() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-Ctarget-cpu=x86-64-v2");
+
+    BuildDetails::none()
+        .require(BuildDetail::TargetCpu)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const TARGET_CPU: &\'static str = \"x86-64-v2\";\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn panic_strategy() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-C\x1fpanic=abort");
+
+    BuildDetails::none()
+        .require(BuildDetail::PanicStrategy)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("RUSTFLAGS");
+
+    BuildDetails::none()
+        .require(BuildDetail::PanicStrategy)
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::PanicStrategy)
+        .panic_strategy_fallback(None)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const PANIC_STRATEGY: &\'static str = \"abort\";
+pub const PANIC_STRATEGY: &\'static str = \"unwind\";
+pub const PANIC_STRATEGY: Option<&\'static str> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn lto_kind() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::remove_var("RUSTFLAGS");
+
+    let flags = [
+        ("-Clto", "3"),
+        ("-C\x1flto=thin", "3"),
+        ("-Clto=off", "3"),
+        ("", "3"),
+        ("-Ccodegen-units=1", "3"),
+        ("", "0"),
+        ("-Clto=bogus", "3"),
+    ];
+
+    for &(flags, opt_level) in &flags {
+        ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", flags);
+        ::std::env::set_var("OPT_LEVEL", opt_level);
+
+        BuildDetails::none()
+            .include(BuildDetail::LtoKind)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("OPT_LEVEL");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const LTO_KIND: Option<&\'static str> = Some(\"fat\");
+pub const LTO_KIND: Option<&\'static str> = Some(\"thin\");
+pub const LTO_KIND: Option<&\'static str> = Some(\"off\");
+pub const LTO_KIND: Option<&\'static str> = Some(\"thin-local\");
+pub const LTO_KIND: Option<&\'static str> = Some(\"off\");
+pub const LTO_KIND: Option<&\'static str> = Some(\"off\");
+pub const LTO_KIND: Option<&\'static str> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn static_crt() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var(
+        "CARGO_ENCODED_RUSTFLAGS",
+        "-Ctarget-feature=+crt-static\x1f-C\x1ftarget-feature=+avx2,-crt-static\x1f\
+         --codegen=target-feature=+sse4.2,+crt-static",
+    );
+
+    BuildDetails::none()
+        .require(BuildDetail::StaticCrt)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var(
+        "CARGO_ENCODED_RUSTFLAGS",
+        "-Ctarget-feature=+crt-static,-crt-static",
+    );
+
+    BuildDetails::none()
+        .include(BuildDetail::StaticCrt)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("RUSTFLAGS");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const STATIC_CRT: bool = true;\n\
+         pub const STATIC_CRT: Option<bool> = Some(false);\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn default_target_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::DefaultTarget)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("rustc").arg("-vV").output().unwrap();
+    let version = String::from_utf8(output.stdout).unwrap();
+    let host = version
+        .lines()
+        .filter_map(|x| x.strip_prefix("host: "))
+        .next()
+        .unwrap();
+
+    let expected = format!("pub const DEFAULT_TARGET: &\'static str = {:?};\n", host);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn rustc_host_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::RustcHost)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let output = Command::new("rustc").arg("-vV").output().unwrap();
+    let version = String::from_utf8(output.stdout).unwrap();
+    let host = version
+        .lines()
+        .filter_map(|x| x.strip_prefix("host: "))
+        .next()
+        .unwrap();
+
+    let expected = format!("pub const RUSTC_HOST: &\'static str = {:?};\n", host);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn sanitizers() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var(
+        "CARGO_ENCODED_RUSTFLAGS",
+        "-Zsanitizer=thread\x1f-Z\x1fsanitizer=address,leak\x1f-Zbuild-std",
+    );
+
+    BuildDetails::none()
+        .require(BuildDetail::Sanitizers)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("RUSTFLAGS");
+
+    BuildDetails::none()
+        .require(BuildDetail::Sanitizers)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const SANITIZERS: &\'static [&\'static str] = &[
+    \"address\",
+    \"leak\",
+    \"thread\",
+];
+pub const SANITIZERS: &\'static [&\'static str] = &[
+];\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn target_cpu_fallback() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("RUSTFLAGS");
+
+    BuildDetails::none()
+        .include(BuildDetail::TargetCpu)
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::none()
+        .include(BuildDetail::TargetCpu)
+        .target_cpu_fallback(None)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const TARGET_CPU: Option<&\'static str> = Some(\"default\");
+pub const TARGET_CPU: Option<&\'static str> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn only_for_target() {
+    let mut file = tempfile().unwrap();
+
+    let lock = TARGET.lock().unwrap();
+
+    ::std::env::set_var("TARGET", "x86_64-pc-windows-msvc");
+    ::std::env::set_var("CARGO_CFG_TARGET_OS", "windows");
+    ::std::env::set_var("CARGO_CFG_TARGET_FAMILY", "windows");
+
+    BuildDetails::none()
+        .include(BuildDetail::Name)
+        .only_for_target(BuildDetail::Name, |t| t.has_family("unix"))
+        .only_for_target(BuildDetail::Version, |t| t.os() == Some("windows"))
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const VERSION: Option<&\'static str> = option_env!(\"CARGO_PKG_VERSION\");\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn build_host_os_required() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::BuildHostOs)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = format!(
+        "pub const BUILD_HOST_OS: &\'static str = \"{}",
+        ::std::env::consts::OS
+    );
+
+    assert!(actual.starts_with(&expected));
+}
+
+#[test]
+fn namespaced() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-Ctarget-cpu=native");
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .require(BuildDetail::TargetCpu)
+        .namespaced(true)
+        .serde_derive(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.starts_with(
+        "pub mod pkg {
+    pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+}
+pub mod toolchain {
+    pub const TARGET_CPU: &\'static str = \"native\";
+}
+"
+    ));
+
+    assert!(actual.contains("    version: pkg::VERSION,\n"));
+    assert!(actual.contains("    target_cpu: toolchain::TARGET_CPU,\n"));
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn impl_on() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-Ctarget-cpu=native");
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .require(BuildDetail::TargetCpu)
+        .order(&[BuildDetail::Version, BuildDetail::TargetCpu])
+        .namespaced(true)
+        .use_static(true)
+        .serde_derive(true)
+        .impl_on("Build")
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.starts_with(
+        "pub struct Build;
+impl Build {
+    pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+    pub const TARGET_CPU: &\'static str = \"native\";
+}
+"
+    ));
+
+    assert!(actual.contains("    version: Build::VERSION,\n"));
+    assert!(actual.contains("    target_cpu: Build::TARGET_CPU,\n"));
+
+    ::std::mem::drop(lock);
+}
+
+lazy_static! {
+    static ref INCREMENTAL: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn build_flags() {
+    let mut file = tempfile().unwrap();
+
+    let rustflags_lock = RUSTFLAGS.lock().unwrap();
+    let incremental_lock = INCREMENTAL.lock().unwrap();
+
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-Ctarget-feature=+crt-static");
+    ::std::env::set_var("CARGO_INCREMENTAL", "0");
+
+    BuildDetails::none()
+        .require(BuildDetail::StaticCrt)
+        .require(BuildDetail::Incremental)
+        .include(BuildDetail::Profile)
+        .order(&[BuildDetail::StaticCrt, BuildDetail::Incremental])
+        .build_flags(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+    ::std::env::remove_var("CARGO_INCREMENTAL");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let lines: Vec<_> = actual.lines().skip(3).collect();
+
+    assert_eq!(
+        vec![
+            "pub const BUILD_FLAG_INCREMENTAL: u32 = 1u32 << 0;",
+            "pub const BUILD_FLAG_STATIC_CRT: u32 = 1u32 << 1;",
+            "pub const BUILD_FLAGS: u32 = 2u32;",
+        ],
+        lines
+    );
+
+    ::std::mem::drop(incremental_lock);
+    ::std::mem::drop(rustflags_lock);
+}
+
+#[test]
+fn incremental_spellings() {
+    let mut file = tempfile().unwrap();
+
+    let lock = INCREMENTAL.lock().unwrap();
+
+    for value in &["1", "yes", "off", "maybe"] {
+        ::std::env::set_var("CARGO_INCREMENTAL", value);
+
+        BuildDetails::none()
+            .include(BuildDetail::Incremental)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::remove_var("CARGO_INCREMENTAL");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const INCREMENTAL: Option<bool> = Some(true);
+pub const INCREMENTAL: Option<bool> = Some(true);
+pub const INCREMENTAL: Option<bool> = Some(false);
+pub const INCREMENTAL: Option<bool> = None;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn incremental_from_rustflags() {
+    let mut file = tempfile().unwrap();
+
+    let rustflags = RUSTFLAGS.lock().unwrap();
+    let incremental = INCREMENTAL.lock().unwrap();
+
+    ::std::env::remove_var("CARGO_INCREMENTAL");
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-Cincremental=/tmp/incr");
+
+    BuildDetails::none()
+        .require(BuildDetail::Incremental)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!("pub const INCREMENTAL: bool = true;\n", &actual);
+
+    ::std::mem::drop(incremental);
+    ::std::mem::drop(rustflags);
+}
+
+#[test]
+fn dump() {
+    BuildDetails::default().dump().unwrap();
+}
+
+#[test]
+fn no_cargo_directives() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    BuildDetails::all()
+        .serde_derive(true)
+        .lazy_build_info(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::all()
+        .namespaced(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.lines().all(|line| !line.starts_with("cargo:")));
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn map_value() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .include(BuildDetail::Name)
+        .map_value(BuildDetail::Profile, |x| x.to_uppercase())
+        .map_value(BuildDetail::Name, |x| format!("{}!", x))
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const NAME: Option<&\'static str> = Some(\"build_details!\");
+pub const PROFILE: &\'static str = \"RELEASE\";\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn formatter() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "/opt/builds/release/");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .formatter(BuildDetail::Profile, formatters::basename)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!("pub const PROFILE: &\'static str = \"release\";\n", &actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn builtin_formatters() {
+    assert_eq!(
+        "a".repeat(37) + "...",
+        formatters::truncate_40(&"a".repeat(41))
+    );
+    assert_eq!("short", formatters::truncate_40("short"));
+
+    assert_eq!("project", formatters::basename("/home/me/project/"));
+    assert_eq!("main.rs", formatters::basename("src\\main.rs"));
+    assert_eq!("/", formatters::basename("/"));
+
+    assert_eq!(
+        "https://example.com",
+        formatters::https_only("http://example.com")
+    );
+    assert_eq!(
+        "https://example.com",
+        formatters::https_only("https://example.com")
+    );
+    assert_eq!("git@example.com", formatters::https_only("git@example.com"));
+}
+
+#[test]
+fn require_these() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::require_these(&[BuildDetail::Profile])
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!("pub const PROFILE: &\'static str = \"release\";\n", &actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn detail_manifest() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::none()
+        .include(BuildDetail::Version)
+        .require(BuildDetail::Profile)
+        .detail_manifest(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const VERSION: Option<&\'static str> = option_env!(\"CARGO_PKG_VERSION\");
+pub const PROFILE: &\'static str = \"release\";
+pub const DETAIL_MANIFEST: &\'static [(&\'static str, bool)] = &[
+    (\"VERSION\", false),
+    (\"PROFILE\", true),
+];\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn toolchain_name() {
+    let mut file = tempfile().unwrap();
+
+    let toolchain = ::std::env::var("RUSTUP_TOOLCHAIN").ok();
+
+    ::std::env::set_var("RUSTUP_TOOLCHAIN", "stable-x86_64-unknown-linux-gnu");
+
+    BuildDetails::none()
+        .include(BuildDetail::ToolchainName)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("RUSTUP_TOOLCHAIN");
+
+    BuildDetails::none()
+        .include(BuildDetail::ToolchainName)
+        .write_to(&mut file)
+        .unwrap();
+
+    if let Some(toolchain) = toolchain {
+        ::std::env::set_var("RUSTUP_TOOLCHAIN", toolchain);
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const TOOLCHAIN: Option<&\'static str> = Some(\"stable-x86_64-unknown-linux-gnu\");
+pub const TOOLCHAIN: Option<&\'static str> = None;\n",
+        &actual
+    );
+}
+
+#[test]
+fn truncate() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "r\u{e9}lease");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .truncate(BuildDetail::Profile, 5)
+        .write_to(&mut file)
+        .unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .truncate(BuildDetail::Profile, 7)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("PROFILE", "debug");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .truncate(BuildDetail::Profile, 5)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const PROFILE: &\'static str = \"r...\";
+pub const PROFILE: &\'static str = \"r\u{e9}l...\";
+pub const PROFILE: &\'static str = \"debug\";\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+lazy_static! {
+    static ref VERSION: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn version_gate() {
+    let mut file = tempfile().unwrap();
+
+    let lock = VERSION.lock().unwrap();
+
+    BuildDetails::none()
+        .version_gate(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::mem::drop(lock);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "#[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]
+pub const fn is_at_least(major: u64, minor: u64, patch: u64) -> bool {
+    if major != 0u64 {
+        return major < 0u64;
+    }
+    if minor != 1u64 {
+        return minor < 1u64;
+    }
+    patch <= 2u64
+}\n",
+        &actual
+    );
+}
+
+#[test]
+fn optional_wrapper() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    BuildDetails::none()
+        .include(BuildDetail::Version)
+        .require(BuildDetail::Profile)
+        .optional_wrapper("Maybe", "Maybe::Just", "Maybe::Nothing")
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("PROFILE");
+
+    BuildDetails::none()
+        .include(BuildDetail::Profile)
+        .optional_wrapper("Maybe", "Maybe::Just", "Maybe::Nothing")
+        .placeholder_missing("unknown")
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const VERSION: Maybe<&\'static str> = match option_env!(\"CARGO_PKG_VERSION\") { Some(x) => Maybe::Just(x), None => Maybe::Nothing };
+pub const PROFILE: &\'static str = \"release\";
+pub const PROFILE: Maybe<&\'static str> = Maybe::Nothing;\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn profile_specific() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    let mut details = BuildDetails::none();
+    details
+        .require(BuildDetail::Profile)
+        .profile_specific("debug", BuildDetail::Profile)
+        .profile_specific("bench", BuildDetail::Profile)
+        .profile_specific("debug", BuildDetail::Version);
+
+    for profile in &["debug", "release", "bench"] {
+        ::std::env::set_var("PROFILE", profile);
+        details.write_to(&mut file).unwrap();
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let mut lines: Vec<_> = actual.lines().collect();
+    lines[..2].sort();
+
+    assert_eq!(
+        vec![
+            "pub const PROFILE: &\'static str = \"debug\";",
+            "pub const VERSION: Option<&\'static str> = option_env!(\"CARGO_PKG_VERSION\");",
+            "pub const PROFILE: &\'static str = \"bench\";",
+        ],
+        lines
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn inspect() {
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "release");
+
+    let mut seen = vec![];
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .include_env_list("UNSET", "BUILD_DETAILS_TEST_INSPECT", ';')
+        .inspect(|name, value| seen.push((name.to_owned(), value.map(str::to_owned))))
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            ("PROFILE".to_owned(), Some("release".to_owned())),
+            ("UNSET".to_owned(), None),
+        ],
+        seen
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn phf_path() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Cfg)
+        .phf_path("::reexports::phf")
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.starts_with(
+        "pub const CFG: ::reexports::phf::Map<&\'static str, &\'static str> = \
+         ::reexports::phf::Map {"
+    ));
+}
+
+#[test]
+fn ordered_cfg() {
+    ::std::env::set_var("CARGO_CFG_ZZ_ORDERED_TEST", "last");
+    ::std::env::set_var("CARGO_CFG_AA_ORDERED_TEST", "first");
+
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Cfg)
+        .ordered_cfg(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_CFG_ZZ_ORDERED_TEST");
+    ::std::env::remove_var("CARGO_CFG_AA_ORDERED_TEST");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.starts_with(
+        "pub const CFG: ::phf::OrderedMap<&\'static str, &\'static str> = \
+         ::phf::OrderedMap {"
+    ));
+
+    let first = actual.find("\"AA_ORDERED_TEST\"").unwrap();
+    let last = actual.find("\"ZZ_ORDERED_TEST\"").unwrap();
+    assert!(first < last);
+}
+
+#[test]
+fn track_binary_size() {
+    let mut file = tempfile().unwrap();
+
+    let dir = tempdir().unwrap();
+    let binary = dir.path().join("app");
+
+    BuildDetails::none()
+        .track_binary_size(binary.clone())
+        .write_to(&mut file)
+        .unwrap();
+
+    fs::write(&binary, [0u8; 1234]).unwrap();
+
+    let mut details = BuildDetails::none();
+    details.track_binary_size(binary);
+    details.write_to(&mut file).unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const PREV_BINARY_SIZE: Option<u64> = None;
+pub const PREV_BINARY_SIZE: Option<u64> = Some(1234u64);\n",
+        &actual
+    );
+
+    let report = details.reproducibility_report();
+    let flagged: Vec<_> = report.outputs().iter().map(|x| x.name()).collect();
+    assert_eq!(vec!["PREV_BINARY_SIZE"], flagged);
+}
+
+#[test]
+fn include_map_duplicate_keys() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .include_map(
+            "SERVERS",
+            &[
+                ("eu", "old.example.com"),
+                ("us", "us.example.com"),
+                ("eu", "eu.example.com"),
+            ],
+        )
+        .ordered_cfg(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.starts_with(
+        "pub const SERVERS: ::phf::OrderedMap<&\'static str, &\'static str> = \
+         ::phf::OrderedMap {"
+    ));
+
+    assert!(!actual.contains("old.example.com"));
+
+    let eu = actual.find("(\"eu\", \"eu.example.com\")").unwrap();
+    let us = actual.find("(\"us\", \"us.example.com\")").unwrap();
+    assert!(eu < us);
+}
+
+#[test]
+fn version_build() {
+    let mut file = tempfile().unwrap();
+
+    let lock = VERSION.lock().unwrap();
+
+    for version in &["1.0.0-beta+exp.sha.5114f85", "1.0.0"] {
+        ::std::env::set_var("CARGO_PKG_VERSION", version);
+
+        BuildDetails::none()
+            .require(BuildDetail::VersionBuild)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::set_var("CARGO_PKG_VERSION", env!("CARGO_PKG_VERSION"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const VERSION_BUILD: &\'static str = \"exp.sha.5114f85\";
+pub const VERSION_BUILD: &\'static str = \"\";\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn with_semver_accessor() {
+    let mut file = tempfile().unwrap();
+
+    let lock = VERSION.lock().unwrap();
+
+    for version in &["1.02.3-beta-2+exp.sha.5114f85", "1.0.0+build", "1.0.0"] {
+        ::std::env::set_var("CARGO_PKG_VERSION", version);
+
+        BuildDetails::none()
+            .with_semver_accessor(true)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::set_var("CARGO_PKG_VERSION", env!("CARGO_PKG_VERSION"));
+
+    ::std::mem::drop(lock);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub fn semver() -> &\'static str {
+    \"1.2.3-beta-2+exp.sha.5114f85\"
+}
+pub fn semver() -> &\'static str {
+    \"1.0.0+build\"
+}
+pub fn semver() -> &\'static str {
+    \"1.0.0\"
+}\n",
+        &actual
+    );
+}
+
+#[test]
+fn author_count() {
+    let mut file = tempfile().unwrap();
+
+    for authors in &["Ann <ann@example.com>:Bob:Cy", "Solo", ""] {
+        ::std::env::set_var("CARGO_PKG_AUTHORS", authors);
+
+        BuildDetails::none()
+            .require(BuildDetail::AuthorCount)
+            .write_to(&mut file)
+            .unwrap();
+    }
+
+    ::std::env::set_var("CARGO_PKG_AUTHORS", env!("CARGO_PKG_AUTHORS"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const AUTHOR_COUNT: u32 = 3u32;
+pub const AUTHOR_COUNT: u32 = 1u32;
+pub const AUTHOR_COUNT: u32 = 0u32;\n",
+        &actual
+    );
+}
+
+lazy_static! {
+    static ref FEATURES: Mutex<()> = Mutex::new(());
+}
+
+#[test]
+fn features_string() {
+    let mut file = tempfile().unwrap();
+
+    let lock = FEATURES.lock().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::FeaturesString)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_FEATURE_ZED", "1");
+    ::std::env::set_var("CARGO_FEATURE_ALPHA", "1");
+
+    BuildDetails::none()
+        .require(BuildDetail::FeaturesString)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_FEATURE_ZED");
+    ::std::env::remove_var("CARGO_FEATURE_ALPHA");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const FEATURES_STRING: &\'static str = \"\";
+pub const FEATURES_STRING: &\'static str = \"ALPHA,ZED\";\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn feature_flags() {
+    let mut file = tempfile().unwrap();
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"flags\"\n\n\
+         [features]\n\
+         default = [\"std\"]\n\
+         std = []\n\
+         \"serde-json\" = [\n    \"std\",\n]\n\
+         extra = [] # unused\n\n\
+         [dependencies]\n\
+         log = \"0.4\"\n",
+    )
+    .unwrap();
+
+    let manifest_lock = MANIFEST_DIR.lock().unwrap();
+    let features_lock = FEATURES.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", dir.path());
+    ::std::env::set_var("CARGO_FEATURE_STD", "1");
+    ::std::env::set_var("CARGO_FEATURE_SERDE_JSON", "1");
+
+    BuildDetails::none()
+        .require(BuildDetail::FeatureFlags)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_FEATURE_STD");
+    ::std::env::remove_var("CARGO_FEATURE_SERDE_JSON");
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const FEATURES_AVAILABLE: &\'static [&\'static str] = &[
+    \"default\",
+    \"extra\",
+    \"serde-json\",
+    \"std\",
+];
+pub const FEATURES_ENABLED: &\'static [&\'static str] = &[
+    \"serde-json\",
+    \"std\",
+];\n",
+        &actual
+    );
+
+    ::std::mem::drop(features_lock);
+    ::std::mem::drop(manifest_lock);
+}
+
+#[test]
+fn optional_deps() {
+    let mut file = tempfile().unwrap();
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"optional\"\n\n\
+         [dependencies]\n\
+         log = \"0.4\"\n\
+         serde_json = { version = \"1\", optional = true }\n\
+         \"rand-core\" = { version = \"0.6\", optional = true } # seeding\n\n\
+         [dependencies.regex]\n\
+         version = \"1\"\n\
+         optional = true\n\n\
+         [target.'cfg(unix)'.dependencies]\n\
+         libc = { version = \"0.2\", optional = true }\n\n\
+         [dev-dependencies]\n\
+         tempfile = { version = \"3\", optional = true }\n",
+    )
+    .unwrap();
+
+    let manifest_lock = MANIFEST_DIR.lock().unwrap();
+    let features_lock = FEATURES.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", dir.path());
+    ::std::env::set_var("CARGO_FEATURE_RAND_CORE", "1");
+    ::std::env::set_var("CARGO_FEATURE_REGEX", "1");
+    ::std::env::set_var("CARGO_FEATURE_TEMPFILE", "1");
+
+    BuildDetails::none()
+        .require(BuildDetail::OptionalDeps)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("CARGO_FEATURE_RAND_CORE");
+    ::std::env::remove_var("CARGO_FEATURE_REGEX");
+    ::std::env::remove_var("CARGO_FEATURE_TEMPFILE");
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const OPTIONAL_DEPS_ENABLED: &\'static [&\'static str] = &[
+    \"rand-core\",
+    \"regex\",
+];\n",
+        &actual
+    );
+
+    ::std::mem::drop(features_lock);
+    ::std::mem::drop(manifest_lock);
+}
+
+#[test]
+fn list_format_single_line() {
+    let mut file = tempfile().unwrap();
+
+    ::std::env::set_var("BUILD_DETAILS_TEST_LIST_FORMAT", "a;b;c");
+
+    BuildDetails::none()
+        .include_env_list("SOME", "BUILD_DETAILS_TEST_LIST_FORMAT", ';')
+        .include_env_list("EMPTY", "BUILD_DETAILS_TEST_LIST_FORMAT_EMPTY", ';')
+        .include_env_list("UNSET", "BUILD_DETAILS_TEST_LIST_FORMAT_UNSET", ';')
+        .list_format(ListFormat::SingleLine)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("BUILD_DETAILS_TEST_LIST_FORMAT_EMPTY", "");
+
+    BuildDetails::none()
+        .include_env_list("EMPTY", "BUILD_DETAILS_TEST_LIST_FORMAT_EMPTY", ';')
+        .list_format(ListFormat::SingleLine)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("BUILD_DETAILS_TEST_LIST_FORMAT");
+    ::std::env::remove_var("BUILD_DETAILS_TEST_LIST_FORMAT_EMPTY");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const SOME: Option<&\'static [&\'static str]> = Some(&[\"a\", \"b\", \"c\"]);
+pub const EMPTY: Option<&\'static [&\'static str]> = None;
+pub const UNSET: Option<&\'static [&\'static str]> = None;
+pub const EMPTY: Option<&\'static [&\'static str]> = Some(&[]);\n",
+        &actual
+    );
+}
+
+#[test]
+fn categories_keywords() {
+    let mut file = tempfile().unwrap();
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"listed\"\n\
+         keywords = [\"build\", 'git'] # searchable\n\
+         categories = [\n    \"development-tools::build-utils\",\n]\n\n\
+         [badges]\n\
+         keywords = [\"badge\"]\n",
+    )
+    .unwrap();
+
+    let lock = MANIFEST_DIR.lock().unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", dir.path());
+
+    BuildDetails::none()
+        .require(BuildDetail::Categories)
+        .require(BuildDetail::Keywords)
+        .order(&[BuildDetail::Categories, BuildDetail::Keywords])
+        .write_to(&mut file)
+        .unwrap();
+
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"bare\"\n",
+    )
+    .unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Keywords)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const CATEGORIES: &\'static [&\'static str] = &[
+    \"development-tools::build-utils\",
+];
+pub const KEYWORDS: &\'static [&\'static str] = &[
+    \"build\",
+    \"git\",
+];
+pub const KEYWORDS: &\'static [&\'static str] = &[
+];\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn include_env_list() {
+    let mut file = tempfile().unwrap();
+
+    ::std::env::set_var("BUILD_DETAILS_TEST_LIST", "a;b;c");
+    ::std::env::set_var("BUILD_DETAILS_TEST_EMPTY", "");
+    ::std::env::remove_var("BUILD_DETAILS_TEST_UNSET");
+
+    BuildDetails::none()
+        .include_env_list("LIST", "BUILD_DETAILS_TEST_LIST", ';')
+        .include_env_list("EMPTY", "BUILD_DETAILS_TEST_EMPTY", ';')
+        .include_env_list("UNSET", "BUILD_DETAILS_TEST_UNSET", ';')
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const LIST: Option<&\'static [&\'static str]> = Some(&[
+    \"a\",
+    \"b\",
+    \"c\",
+]);
+pub const EMPTY: Option<&\'static [&\'static str]> = Some(&[
+]);
+pub const UNSET: Option<&\'static [&\'static str]> = None;\n",
+        &actual
+    );
+}
+
+#[test]
+fn strict_conflicts() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    let mut details = BuildDetails::all();
+    details
+        .require(BuildDetail::Version)
+        .include(BuildDetail::Name)
+        .require(BuildDetail::Name)
+        .strict_conflicts(true);
+
+    let result = details.write_to(&mut file).unwrap_err();
+
+    match result {
+        Error::ConflictingRequirement(BuildDetail::Name) => (),
+        _ => panic!("Expected Error::ConflictingRequirement(Name)"),
+    }
+
+    details.strict_conflicts(false).write_to(&mut file).unwrap();
+
+    details
+        .exclude(BuildDetail::Name)
+        .require(BuildDetail::Name)
+        .strict_conflicts(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn post_process() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .post_process(|code| Ok(format!("// @generated\n{}", code)))
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "// @generated\npub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");\n",
+        actual
+    );
+
+    let result = BuildDetails::none()
+        .require(BuildDetail::Version)
+        .post_process(|_| Err(Error::MissingEnv("FORMATTER")))
+        .write_to(&mut file)
+        .unwrap_err();
+
+    match result {
+        Error::MissingEnv("FORMATTER") => (),
+        _ => panic!("Expected Error::MissingEnv(FORMATTER)"),
+    }
+}
+
+#[test]
+fn max_output_bytes() {
+    let mut file = tempfile().unwrap();
+
+    let mut details = BuildDetails::none();
+    details.require(BuildDetail::Version);
+
+    // pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");\n
+    details.max_output_bytes(61).write_to(&mut file).unwrap();
+
+    let result = details
+        .max_output_bytes(60)
+        .write_to(&mut file)
+        .unwrap_err();
+
+    match result {
+        Error::OutputTooLarge(61) => (),
+        _ => panic!("Expected Error::OutputTooLarge(61)"),
+    }
+}
+
+#[test]
+fn include_env_list_invalid_name() {
+    let mut file = tempfile().unwrap();
+
+    let result = BuildDetails::none()
+        .include_env_list("NOT-VALID", "PATH", ':')
+        .write_to(&mut file)
+        .unwrap_err();
+
+    match result {
+        Error::InvalidConstName(ref x) if x == "NOT-VALID" => (),
+        _ => panic!("Expected Error::InvalidConstName(NOT-VALID)"),
+    }
+}
+
+#[test]
+fn auto_build_number() {
+    let dir = tempdir().unwrap();
+    let counter = dir.path().join("build-number");
+
+    // Left behind by a build that was killed while holding the lock.
+    fs::write(dir.path().join("build-number.lock"), "").unwrap();
+
+    let mut file = tempfile().unwrap();
+
+    let mut details = BuildDetails::none();
+    details.auto_build_number(counter.clone());
+
+    details.write_to(&mut file).unwrap();
+    details.write_to(&mut file).unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BUILD_NUMBER: u64 = 1u64;
+pub const BUILD_NUMBER: u64 = 2u64;\n",
+        &actual
+    );
+    assert_eq!("2", fs::read_to_string(&counter).unwrap().trim());
+
+    let report = details.reproducibility_report();
+    let flagged: Vec<_> = report.outputs().iter().map(|x| x.name()).collect();
+    assert_eq!(vec!["BUILD_NUMBER"], flagged);
+    assert_eq!("2", fs::read_to_string(&counter).unwrap().trim());
+}
+
+#[test]
+fn auto_build_number_concurrent() {
+    let dir = tempdir().unwrap();
+    let counter = dir.path().join("build-number");
+
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let counter = counter.clone();
+            ::std::thread::spawn(move || {
+                BuildDetails::none()
+                    .auto_build_number(counter)
+                    .write_to(&mut Vec::new())
+                    .unwrap();
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!("8", fs::read_to_string(&counter).unwrap().trim());
+}
+
+#[test]
+fn generate_region() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("info.rs");
+
+    fs::write(
+        &path,
+        "fn before() {}
+// BEGIN details
+pub const OLD: u32 = 1u32;
+// END details
+fn after() {}\n",
+    )
+    .unwrap();
+
+    BuildDetails::none()
+        .include_env_list("LIST", "BUILD_DETAILS_TEST_REGION", ',')
+        .generate_region(&path, "BEGIN details", "END details")
+        .unwrap();
+
+    assert_eq!(
+        "fn before() {}
+// BEGIN details
+pub const LIST: Option<&\'static [&\'static str]> = None;
+// END details
+fn after() {}\n",
+        fs::read_to_string(&path).unwrap()
+    );
+}
+
+#[test]
+fn generate_region_invalid_markers() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("info.rs");
+
+    let original = "// END details\n// BEGIN details\n";
+    fs::write(&path, original).unwrap();
+
+    let missing = BuildDetails::none()
+        .generate_region(&path, "BEGIN details", "STOP details")
+        .unwrap_err();
+
+    match missing {
+        Error::InvalidRegion(ref x) if x == "STOP details" => (),
+        _ => panic!("Expected Error::InvalidRegion(STOP details)"),
+    }
+
+    let unbalanced = BuildDetails::none()
+        .generate_region(&path, "BEGIN details", "END details")
+        .unwrap_err();
+
+    match unbalanced {
+        Error::InvalidRegion(ref x) if x == "END details" => (),
+        _ => panic!("Expected Error::InvalidRegion(END details)"),
+    }
+
+    assert_eq!(original, fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn include_file() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("banner.txt"), "Hello, \"world\"\n").unwrap();
+    fs::write(dir.path().join("data.bin"), [0u8, 1, b'a', 0xff]).unwrap();
+
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .include_file("BANNER", dir.path().join("banner.txt"))
+        .include_file("ABSENT", dir.path().join("absent.txt"))
+        .include_file_bytes("DATA", dir.path().join("data.bin"))
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BANNER: Option<&\'static str> = Some(\"Hello, \\\"world\\\"\\n\");
+pub const ABSENT: Option<&\'static str> = None;
+pub const DATA: Option<&\'static [u8]> = Some(b\"\\x00\\x01a\\xff\" as &[u8]);\n",
+        &actual
+    );
+}
+
+#[test]
+fn rerun_inputs() {
+    let dir = tempdir().unwrap();
+    let banner = dir.path().join("banner.txt");
+
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::RerunInputs)
+        .include(BuildDetail::ToolchainName)
+        .include_file("BANNER", banner.clone())
+        .include_file("AGAIN", banner.clone())
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = format!(
+        "pub const RERUN_INPUTS: &\'static [&\'static str] = &[
+    {:?},
+    \"rerun-if-env-changed=RUSTUP_TOOLCHAIN\",
+];",
+        format!("rerun-if-changed={}", banner.display())
+    );
+
+    assert!(actual.contains(&expected));
+}
+
+#[test]
+fn include_file_not_utf8() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("data.bin");
+
+    fs::write(&path, [0xffu8, 0xfe]).unwrap();
+
+    let result = BuildDetails::none()
+        .include_file("DATA", path)
+        .write_to(&mut Vec::new())
+        .unwrap_err();
+
+    match result {
+        Error::Io(ref e) if e.kind() == ::std::io::ErrorKind::InvalidData => (),
+        _ => panic!("Expected Error::Io(InvalidData)"),
+    }
+}
+
+#[test]
+fn c_header() {
+    let mut file = tempfile().unwrap();
+
+    let lock = PROFILE.lock().unwrap();
+
+    ::std::env::set_var("PROFILE", "r\u{e9}lease \"?\"");
+
+    BuildDetails::none()
+        .require(BuildDetail::Profile)
+        .include(BuildDetail::Cfg)
+        .write_c_header(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "#ifndef BUILD_DETAILS_H
+#define BUILD_DETAILS_H
+#define PROFILE \"r\\303\\251lease \\\"\\?\\\"\"
+#endif\n",
+        &actual
+    );
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn c_header_banner() {
+    let mut file = tempfile().unwrap();
+
+    let lock = SOURCE_DATE_EPOCH.lock().unwrap();
+
+    ::std::env::set_var("SOURCE_DATE_EPOCH", "1500000000");
+
+    BuildDetails::none()
+        .output_banner(true)
+        .write_c_header(&mut file)
+        .unwrap();
+
+    ::std::env::remove_var("SOURCE_DATE_EPOCH");
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    let expected = format!(
+        "/* Generated by build_details {} at 2017-07-14T02:40:00+00:00 */
+#ifndef BUILD_DETAILS_H
+#define BUILD_DETAILS_H
+#endif\n",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    assert_eq!(expected, actual);
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn c_header_bom() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .with_bom(true)
+        .write_c_header(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = vec![];
+    file.read_to_end(&mut actual).unwrap();
+
+    assert_eq!(
+        &b"\xef\xbb\xbf#ifndef BUILD_DETAILS_H\n#define BUILD_DETAILS_H\n#endif\n"[..],
+        &actual[..]
+    );
+}
+
+#[test]
+fn c_header_numbers() {
+    let mut file = tempfile().unwrap();
+
+    let lock = RUSTFLAGS.lock().unwrap();
+
+    ::std::env::set_var("CARGO_ENCODED_RUSTFLAGS", "-Ccodegen-units=1");
+
+    BuildDetails::none()
+        .require(BuildDetail::CodegenUnits)
+        .write_c_header(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.contains("#define CODEGEN_UNITS 1U\n"));
+
+    ::std::mem::drop(lock);
+}
+
+#[test]
+fn docs_rs_safe() {
+    let mut file = tempfile().unwrap();
+
+    let details = [
+        BuildDetail::Version,
+        BuildDetail::Profile,
+        BuildDetail::CodegenUnits,
+        BuildDetail::Incremental,
+        BuildDetail::Timestamp,
+    ];
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .require(BuildDetail::Profile)
+        .require(BuildDetail::CodegenUnits)
+        .require(BuildDetail::Incremental)
+        .include(BuildDetail::Timestamp)
+        .order(&details)
+        .docs_rs_safe(true)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+pub const PROFILE: &\'static str = \"\";
+pub const CODEGEN_UNITS: u32 = 0u32;
+pub const INCREMENTAL: bool = false;
+pub const TIMESTAMP: Option<u64> = None;\n",
+        &actual
+    );
+
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::RerunInputs)
+        .include(BuildDetail::ToolchainName)
+        .docs_rs_safe(false)
+        .write_to(&mut file)
+        .unwrap();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert!(actual.contains(
+        "pub const RERUN_INPUTS: &\'static [&\'static str] = &[
+    \"rerun-if-env-changed=RUSTUP_TOOLCHAIN\",
+];"
+    ));
+}
+
+#[test]
+fn rename_prefix() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::none()
+        .require(BuildDetail::Version)
+        .require(BuildDetail::Name)
+        .order(&[BuildDetail::Version, BuildDetail::Name])
+        .rename(BuildDetail::Version, "PKG_VERSION")
+        .prefix("BUILD_")
+        .write_to(&mut file)
+        .unwrap();
+
+    let result = BuildDetails::none()
+        .require(BuildDetail::Version)
+        .rename(BuildDetail::Version, "PKG VERSION")
+        .write_to(&mut tempfile().unwrap());
+
+    match result {
+        Err(Error::InvalidConstName(name)) => assert_eq!("PKG VERSION", name),
+        other => panic!("expected an invalid name, got {:?}", other),
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut actual = String::new();
+    file.read_to_string(&mut actual).unwrap();
+
+    assert_eq!(
+        "pub const BUILD_PKG_VERSION: &\'static str = env!(\"CARGO_PKG_VERSION\");
+pub const BUILD_NAME: &\'static str = env!(\"CARGO_PKG_NAME\");\n",
+        &actual
+    );
+}
@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "verify")]
+
+extern crate build_details;
+extern crate tempfile;
+
+use build_details::BuildDetails;
+
+use tempfile::tempfile;
+
+#[test]
+fn verify_all() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::all()
+        .serde_derive(true)
+        .lazy_build_info(true)
+        .verify(true)
+        .write_to(&mut file)
+        .unwrap();
+}
+
+#[test]
+fn verify_namespaced() {
+    let mut file = tempfile().unwrap();
+
+    BuildDetails::all()
+        .namespaced(true)
+        .serde_derive(true)
+        .verify(true)
+        .write_to(&mut file)
+        .unwrap();
+}